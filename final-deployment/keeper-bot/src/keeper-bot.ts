@@ -45,6 +45,12 @@ interface KeeperConfig {
   minimum_holder_value_usd: number;
 }
 
+// Pools per registry page, matches the vault's MAX_POOLS
+const MAX_POOLS_PER_PAGE = 50;
+
+// Entries per keeper work log page, matches the vault's KEEPER_LOG_PAGE_SIZE
+const KEEPER_LOG_PAGE_SIZE = 32;
+
 export class KeeperBot {
   private connection: Connection;
   private keeper: Keypair;
@@ -225,29 +231,18 @@ export class KeeperBot {
     const rewardToken = await this.getCurrentRewardToken();
     this.logger.info('Current reward token', { rewardToken: rewardToken.toBase58() });
     
-    // 7. Handle tax distribution with keeper SOL top-up scenarios
+    // 7. Handle tax distribution with keeper SOL top-up
     const solMint = new PublicKey('So11111111111111111111111111111111111111112');
     const isRewardSol = rewardToken.equals(solMint);
     
-    // Handle keeper SOL top-up if needed
-    if (keeperNeedsSol) {
-      const targetBalance = 0.10 * LAMPORTS_PER_SOL; // Target 0.10 SOL
-      const neededSol = Math.max(0, targetBalance - keeperBalance);
-      
-      this.logger.info('Keeper needs SOL top-up', {
-        current: keeperBalance / LAMPORTS_PER_SOL,
-        target: targetBalance / LAMPORTS_PER_SOL,
-        needed: neededSol / LAMPORTS_PER_SOL
-      });
-      
-      // Tax flow implementation - need to handle swaps first
-      // Note: We'll handle keeper top-up after swapping to appropriate token
-    }
-    
-    // 8. Withdraw all harvested fees to keeper for processing
-    this.logger.info('Withdrawing harvested fees to keeper', {
-      amount: harvestResult.amount / 1e9
-    });
+    const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
+    const vaultTokenAccount = getAssociatedTokenAddressSync(
+      this.tokenMint,
+      this.vaultPda,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const holderTreasury = this.configManager.getHolderTreasuryPda();
     
     // Ensure keeper has MIKO token account
     const keeperMikoAccount = getAssociatedTokenAddressSync(
@@ -277,40 +272,132 @@ export class KeeperBot {
       );
     }
     
-    // Withdraw all harvested fees to keeper
-    const withdrawTx = await this.vaultProgram.methods
-      .withdrawHarvestedFees(new BN(Math.floor(harvestResult.amount)))
+    // The split pays the top-up out of the owner share as a keeper allowance
+    let keeperAllowance = 0;
+    if (keeperNeedsSol) {
+      const targetBalance = 0.10 * LAMPORTS_PER_SOL; // Target 0.10 SOL
+      const neededSol = Math.max(0, targetBalance - keeperBalance);
+      
+      // Calculate MIKO needed for SOL
+      const solPrice = await this.pythClient.getSolPrice();
+      const mikoPrice = await this.birdeyeClient.getTokenPrice(this.tokenMint.toBase58());
+      const mikoForSol = Math.ceil((neededSol / LAMPORTS_PER_SOL) * solPrice / mikoPrice * 1.1 * 1e9);
+      const ownerMikoAmount = Math.floor(harvestResult.amount * vaultState.ownerTaxShare.toNumber() / 100);
+      keeperAllowance = Number.isFinite(mikoForSol) ? Math.min(ownerMikoAmount, mikoForSol) : 0;
+      
+      this.logger.info('Keeper needs SOL top-up', {
+        current: keeperBalance / LAMPORTS_PER_SOL,
+        target: targetBalance / LAMPORTS_PER_SOL,
+        needed: neededSol / LAMPORTS_PER_SOL,
+        mikoAllowance: keeperAllowance / 1e9
+      });
+    }
+    
+    // 8. Split harvested fees on-chain, then withdraw the holder share to keeper
+    this.logger.info('Splitting harvested fees', {
+      amount: harvestResult.amount / 1e9
+    });
+    
+    const allowanceBefore = Number(
+      (await getAccount(this.connection, keeperMikoAccount, 'confirmed', TOKEN_2022_PROGRAM_ID)).amount
+    );
+    
+    const splitTx = await this.vaultProgram.methods
+      .splitHarvestedFees(new BN(Math.floor(harvestResult.amount)), new BN(keeperAllowance))
       .accounts({
         vault: this.vaultPda,
         keeperAuthority: this.keeper.publicKey,
         tokenMint: this.tokenMint,
-        vaultTokenAccount: getAssociatedTokenAddressSync(
+        vaultTokenAccount,
+        ownerTokenAccount: getAssociatedTokenAddressSync(
           this.tokenMint,
-          this.vaultPda,
-          true,
+          vaultState.ownerWallet,
+          false,
           TOKEN_2022_PROGRAM_ID
         ),
-        keeperTokenAccount: keeperMikoAccount,
+        holderTreasury,
+        keeperTokenAccount: keeperAllowance > 0 ? keeperMikoAccount : null,
         tokenProgram: TOKEN_2022_PROGRAM_ID,
       })
       .rpc();
+    
+    this.logger.info('Harvested fees split', { signature: splitTx });
+    
+    if (keeperAllowance > 0) {
+      // Count what arrived net of the transfer fee, then swap it to SOL for the keeper
+      const allowanceReceived = Number(
+        (await getAccount(this.connection, keeperMikoAccount, 'confirmed', TOKEN_2022_PROGRAM_ID)).amount
+      ) - allowanceBefore;
+      
+      const topUpResult = await this.swapService.swapTokens(
+        this.tokenMint,
+        solMint,
+        allowanceReceived,
+        this.keeper.publicKey
+      );
       
-    this.logger.info('Withdrew all harvested fees to keeper', {
+      if (topUpResult.success) {
+        this.logger.info('Keeper SOL top-up from owner portion', {
+          mikoUsed: allowanceReceived / 1e9,
+          solReceived: topUpResult.outputAmount / LAMPORTS_PER_SOL
+        });
+        await this.logKeeperWork({
+          keeperTopUp: {
+            inputAmount: new BN(allowanceReceived),
+            outputLamports: new BN(topUpResult.outputAmount),
+          },
+        });
+      } else {
+        this.logger.error('Failed to swap keeper allowance to SOL', topUpResult);
+      }
+    }
+    
+    // Withdraw the holder treasury balance, subject to the on-chain window limits
+    const treasuryBalance = Number(
+      (await getAccount(this.connection, holderTreasury, 'confirmed', TOKEN_2022_PROGRAM_ID)).amount
+    );
+    if (treasuryBalance === 0) {
+      this.logger.info('Holder treasury empty, nothing to distribute');
+      return;
+    }
+    
+    const keeperBefore = Number(
+      (await getAccount(this.connection, keeperMikoAccount, 'confirmed', TOKEN_2022_PROGRAM_ID)).amount
+    );
+    
+    const withdrawTx = await this.vaultProgram.methods
+      .withdrawHarvestedFees(new BN(treasuryBalance))
+      .accounts({
+        vault: this.vaultPda,
+        keeperAuthority: this.keeper.publicKey,
+        tokenMint: this.tokenMint,
+        holderTreasury,
+        keeperTokenAccount: keeperMikoAccount,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+    
+    // Count what arrived net of the transfer fee
+    const holdersMikoAmount = Number(
+      (await getAccount(this.connection, keeperMikoAccount, 'confirmed', TOKEN_2022_PROGRAM_ID)).amount
+    ) - keeperBefore;
+    
+    this.logger.info('Withdrew holder share to keeper', {
       signature: withdrawTx,
-      amount: harvestResult.amount / 1e9
+      amount: holdersMikoAmount / 1e9
     });
     
     // 9. Handle tax flow scenarios
     if (isRewardSol) {
-      // Scenario 1: Reward token is SOL - swap all MIKO to SOL
-      this.logger.info('Reward token is SOL, swapping all MIKO to SOL', {
-        mikoAmount: harvestResult.amount / 1e9
+      // Scenario 1: Reward token is SOL - swap the holder share to SOL
+      this.logger.info('Reward token is SOL, swapping holder MIKO to SOL', {
+        mikoAmount: holdersMikoAmount / 1e9
       });
       
       const swapResult = await this.swapService.swapTokens(
         this.tokenMint,
         solMint,
-        harvestResult.amount,
+        holdersMikoAmount,
         this.keeper.publicKey
       );
       
@@ -319,171 +406,29 @@ export class KeeperBot {
         return;
       }
       
-      const totalSol = swapResult.outputAmount;
       this.logger.info('Swapped MIKO to SOL', {
-        mikoIn: harvestResult.amount / 1e9,
-        solOut: totalSol / LAMPORTS_PER_SOL
+        mikoIn: holdersMikoAmount / 1e9,
+        solOut: swapResult.outputAmount / LAMPORTS_PER_SOL
       });
       
-      // Calculate distribution amounts
-      let keeperSolAmount = 0;
-      let ownerSolAmount = Math.floor(totalSol * this.config.distribution.owner_percent / 100);
-      let holdersSolAmount = totalSol - ownerSolAmount;
-      
-      if (keeperNeedsSol) {
-        // Use up to owner's 20% for keeper top-up
-        const neededSol = Math.max(0, 0.10 * LAMPORTS_PER_SOL - keeperBalance);
-        keeperSolAmount = Math.min(ownerSolAmount, neededSol);
-        ownerSolAmount -= keeperSolAmount;
-        
-        this.logger.info('Keeper SOL top-up allocated', {
-          needed: neededSol / LAMPORTS_PER_SOL,
-          allocated: keeperSolAmount / LAMPORTS_PER_SOL,
-          remainingOwner: ownerSolAmount / LAMPORTS_PER_SOL
-        });
-      }
-      
-      // Transfer SOL to owner (if any remaining after keeper top-up)
-      if (ownerSolAmount > 0) {
-        const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
-        const { SystemProgram } = anchor.web3;
-        
-        const ownerTransferIx = SystemProgram.transfer({
-          fromPubkey: this.keeper.publicKey,
-          toPubkey: vaultState.ownerWallet,
-          lamports: ownerSolAmount,
-        });
-        
-        const ownerTx = new anchor.web3.Transaction().add(ownerTransferIx);
-        const ownerSig = await sendAndConfirmTransaction(
-          this.connection,
-          ownerTx,
-          [this.keeper],
-          { commitment: 'confirmed' }
-        );
-        
-        this.logger.info('Owner SOL distributed', {
-          signature: ownerSig,
-          amount: ownerSolAmount / LAMPORTS_PER_SOL,
-          recipient: vaultState.ownerWallet.toBase58()
-        });
-      }
-      
       // Distribute SOL to holders
-      const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
       this.holderDistributor.setOwnerWallet(vaultState.ownerWallet);
       
-      await this.holderDistributor.distributeToHolders(
+      const distribution = await this.holderDistributor.distributeToHolders(
         0, // No MIKO amount
-        holdersSolAmount,
+        swapResult.outputAmount,
         rewardToken,
         this.keeper.publicKey // Distribute from keeper since they hold the SOL
       );
+      await this.logHolderDistribution(rewardToken, holdersMikoAmount, distribution);
       
     } else {
       // Scenario 2: Reward token is NOT SOL
       this.logger.info('Reward token is not SOL, handling MIKO distribution', {
         rewardToken: rewardToken.toBase58(),
-        mikoAmount: harvestResult.amount / 1e9
+        mikoAmount: holdersMikoAmount / 1e9
       });
       
-      // Calculate base distribution
-      let ownerMikoAmount = Math.floor(harvestResult.amount * this.config.distribution.owner_percent / 100);
-      let holdersMikoAmount = harvestResult.amount - ownerMikoAmount;
-      
-      if (keeperNeedsSol) {
-        // Need to swap some of owner's MIKO to SOL for keeper
-        const targetBalance = 0.10 * LAMPORTS_PER_SOL;
-        const neededSol = Math.max(0, targetBalance - keeperBalance);
-        
-        // Calculate MIKO needed for SOL
-        const solPrice = await this.pythClient.getSolPrice();
-        const mikoPrice = await this.birdeyeClient.getTokenPrice(this.tokenMint.toBase58());
-        const mikoForSol = Math.ceil((neededSol / LAMPORTS_PER_SOL) * solPrice / mikoPrice * 1.1 * 1e9);
-        const keeperMikoAmount = Math.min(ownerMikoAmount, mikoForSol);
-        
-        if (keeperMikoAmount > 0) {
-          // Swap some MIKO to SOL for keeper
-          const swapResult = await this.swapService.swapTokens(
-            this.tokenMint,
-            solMint,
-            keeperMikoAmount,
-            this.keeper.publicKey
-          );
-          
-          if (swapResult.success) {
-            this.logger.info('Keeper SOL top-up from owner portion', {
-              mikoUsed: keeperMikoAmount / 1e9,
-              solReceived: swapResult.outputAmount / LAMPORTS_PER_SOL
-            });
-          }
-          
-          // Adjust owner amount
-          ownerMikoAmount = ownerMikoAmount - keeperMikoAmount;
-        }
-      }
-      
-      // Swap remaining owner portion to reward token
-      if (ownerMikoAmount > 0) {
-        const ownerSwapResult = await this.swapService.swapMikoForToken(
-          this.tokenMint,
-          rewardToken,
-          ownerMikoAmount
-        );
-        
-        if (ownerSwapResult.success) {
-          // Transfer swapped reward tokens to owner
-          const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
-          const ownerRewardAccount = getAssociatedTokenAddressSync(
-            rewardToken,
-            vaultState.ownerWallet,
-            false,
-            rewardToken.equals(solMint) ? TOKEN_PROGRAM_ID : TOKEN_2022_PROGRAM_ID
-          );
-          
-          const keeperRewardAccount = getAssociatedTokenAddressSync(
-            rewardToken,
-            this.keeper.publicKey,
-            false,
-            rewardToken.equals(solMint) ? TOKEN_PROGRAM_ID : TOKEN_2022_PROGRAM_ID
-          );
-          
-          // Create transfer instruction
-          const { createTransferInstruction } = await import('@solana/spl-token');
-          const transferIx = createTransferInstruction(
-            keeperRewardAccount,
-            ownerRewardAccount,
-            this.keeper.publicKey,
-            ownerSwapResult.outputAmount,
-            [],
-            rewardToken.equals(solMint) ? TOKEN_PROGRAM_ID : TOKEN_2022_PROGRAM_ID
-          );
-          
-          const transferTx = new anchor.web3.Transaction().add(transferIx);
-          
-          // Add priority fee
-          const priorityFee = this.configManager.getPriorityFee();
-          transferTx.add(
-            ComputeBudgetProgram.setComputeUnitPrice({
-              microLamports: priorityFee.microLamports,
-            })
-          );
-          
-          const transferSig = await sendAndConfirmTransaction(
-            this.connection,
-            transferTx,
-            [this.keeper],
-            { commitment: 'confirmed' }
-          );
-          
-          this.logger.info('Owner reward tokens transferred', {
-            signature: transferSig,
-            amount: ownerSwapResult.outputAmount,
-            recipient: vaultState.ownerWallet.toBase58()
-          });
-        }
-      }
-      
       // Swap holders' share to reward token
       const holdersSwapResult = await this.swapService.swapMikoForToken(
         this.tokenMint,
@@ -497,15 +442,15 @@ export class KeeperBot {
       }
       
       // Distribute to holders
-      const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
       this.holderDistributor.setOwnerWallet(vaultState.ownerWallet);
       
-      await this.holderDistributor.distributeToHolders(
+      const distribution = await this.holderDistributor.distributeToHolders(
         0, // No MIKO amount (already swapped)
         holdersSwapResult.outputAmount,
         rewardToken,
         this.keeper.publicKey // Distribute from keeper
       );
+      await this.logHolderDistribution(rewardToken, holdersMikoAmount, distribution);
     }
     
     const duration = timer();
@@ -654,18 +599,75 @@ export class KeeperBot {
         return;
       }
       
-      this.logger.info('New pools detected', { count: newPools.length, pools: newPools });
+      this.logger.info('New pools detected', {
+        count: newPools.length,
+        pools: newPools.map(p => p.pool.toBase58())
+      });
       
-      const poolRegistryPda = this.configManager.getPoolRegistryPda();
+      // Load every registry page, the program rejects pools already on any of them
+      const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
+      const pageCount: number = vaultState.poolRegistryPages;
+      const pages: PublicKey[] = [];
+      const pageSizes: number[] = [];
+      const registered = new Set<string>();
+      let targetPage = -1;
       
-      const tx = await this.vaultProgram.methods
-        .updatePoolRegistry(newPools)
-        .accounts({
-          poolRegistry: poolRegistryPda,
-          vault: this.vaultPda,
-          keeperAuthority: this.keeper.publicKey,
-        })
-        .transaction();
+      for (let page = 0; page < pageCount; page++) {
+        const pda = this.configManager.getPoolRegistryPda(page);
+        const registry = await (this.vaultProgram.account as any).poolRegistry.fetch(pda);
+        pages.push(pda);
+        pageSizes.push(registry.pools.length);
+        for (const entry of registry.pools) {
+          registered.add(entry.pool.toBase58());
+        }
+        if (targetPage < 0 && registry.pools.length < MAX_POOLS_PER_PAGE) {
+          targetPage = page;
+        }
+      }
+      
+      const poolsToAdd = newPools.filter(p => !registered.has(p.pool.toBase58()));
+      if (poolsToAdd.length === 0) {
+        this.logger.debug('Detected pools already registered');
+        return;
+      }
+      
+      const tx = new anchor.web3.Transaction();
+      
+      // Open the next page when every existing one is full
+      if (targetPage < 0) {
+        targetPage = pageCount;
+        pages.push(this.configManager.getPoolRegistryPda(targetPage));
+        pageSizes.push(0);
+        tx.add(
+          await this.vaultProgram.methods
+            .initializePoolRegistry(targetPage)
+            .accountsPartial({
+              poolRegistry: pages[targetPage],
+              vault: this.vaultPda,
+              payer: this.keeper.publicKey,
+            })
+            .instruction()
+        );
+      }
+      
+      // Stay within the space left on the target page
+      const batch = poolsToAdd.slice(0, MAX_POOLS_PER_PAGE - pageSizes[targetPage]);
+      
+      tx.add(
+        await this.vaultProgram.methods
+          .updatePoolRegistry(targetPage, batch)
+          .accountsPartial({
+            poolRegistry: pages[targetPage],
+            vault: this.vaultPda,
+            keeperAuthority: this.keeper.publicKey,
+          })
+          .remainingAccounts(
+            pages
+              .filter((_, page) => page !== targetPage)
+              .map(pubkey => ({ pubkey, isSigner: false, isWritable: false }))
+          )
+          .instruction()
+      );
       
       // Add priority fee
       const priorityFee = this.configManager.getPriorityFee();
@@ -715,7 +717,7 @@ export class KeeperBot {
         
         // Harvest fees instruction
         const harvestTx = await this.vaultProgram.methods
-          .harvestFees(batch)
+          .harvestFees()
           .accounts({
             vault: this.vaultPda,
             keeperAuthority: this.keeper.publicKey,
@@ -822,13 +824,29 @@ export class KeeperBot {
     
     try {
       const smartDialPda = this.configManager.getSmartDialPda();
+      const smartDialProgramId = this.configManager.getSmartDialProgramId();
+      
+      // A single-token basket, the dial checks the mint against its token lists
+      const [allowlistEntry] = PublicKey.findProgramAddressSync(
+        [Buffer.from('allowlist'), smartDialPda.toBuffer(), newToken.toBuffer()],
+        smartDialProgramId
+      );
+      const [denylistEntry] = PublicKey.findProgramAddressSync(
+        [Buffer.from('denylist'), smartDialPda.toBuffer(), newToken.toBuffer()],
+        smartDialProgramId
+      );
       
       const tx = await this.smartDialProgram.methods
-        .updateRewardToken(newToken)
-        .accounts({
+        .updateRewardBasket([10_000])
+        .accountsPartial({
           dialState: smartDialPda,
           authority: this.keeper.publicKey,
         })
+        .remainingAccounts([
+          { pubkey: newToken, isSigner: false, isWritable: false },
+          { pubkey: allowlistEntry, isSigner: false, isWritable: false },
+          { pubkey: denylistEntry, isSigner: false, isWritable: false },
+        ])
         .transaction();
       
       const sig = await sendAndConfirmTransaction(
@@ -920,6 +938,49 @@ export class KeeperBot {
     }
   }
   
+  /**
+   * Log the holder distribution on-chain, releasing the MIKO it spent from keeper custody.
+   */
+  async logHolderDistribution(
+    rewardToken: PublicKey,
+    mikoAmount: number,
+    distribution: { recipientsCount: number; totalDistributed: number }
+  ) {
+    await this.logKeeperWork({
+      distributeToHolders: {
+        rewardMint: rewardToken,
+        epoch: new BN(0), // Direct transfers, no on-chain distribution epoch
+        recipientCount: distribution.recipientsCount,
+        inputAmount: new BN(mikoAmount),
+        amount: new BN(Math.floor(distribution.totalDistributed)),
+      },
+    });
+  }
+  
+  /**
+   * Log keeper work on-chain, distributions and top-ups release what they spent from keeper custody.
+   * The vault refuses the next withdrawal while the keeper holds less than its unlogged custody.
+   */
+  async logKeeperWork(work: Record<string, any>) {
+    try {
+      const vaultState = await (this.vaultProgram.account as any).vaultState.fetch(this.vaultPda);
+      const page = Math.floor(vaultState.keeperLogCount.toNumber() / KEEPER_LOG_PAGE_SIZE);
+      
+      const sig = await this.vaultProgram.methods
+        .logKeeperWork(work)
+        .accountsPartial({
+          vault: this.vaultPda,
+          keeperWorkLogPage: this.configManager.getKeeperLogPda(page),
+          keeperAuthority: this.keeper.publicKey,
+        })
+        .rpc();
+      
+      this.logger.info('Keeper work logged', { work: Object.keys(work)[0], signature: sig });
+    } catch (error) {
+      this.logger.error('Failed to log keeper work', error);
+    }
+  }
+  
   /**
    * Helper functions
    */
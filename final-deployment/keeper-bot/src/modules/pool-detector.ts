@@ -4,6 +4,16 @@ import {
 } from '@solana/web3.js';
 import { Logger } from '../utils/logger';
 
+/**
+ * Pool metadata in the shape of the vault's `PoolInfo`
+ */
+export interface DetectedPool {
+  pool: PublicKey;
+  dexProgram: PublicKey;
+  tokenVault: PublicKey; // Pool's MIKO vault account
+  quoteMint: PublicKey;
+}
+
 export class PoolDetector {
   private connection: Connection;
   private logger: Logger;
@@ -22,34 +32,34 @@ export class PoolDetector {
   /**
    * Detect new pools that aren't already in the registry
    */
-  async detectNewPools(tokenMint: PublicKey): Promise<PublicKey[]> {
+  async detectNewPools(tokenMint: PublicKey): Promise<DetectedPool[]> {
     try {
-      const newPools: PublicKey[] = [];
+      const newPools: DetectedPool[] = [];
       
       // Detect CPMM pools
       const cpmmPools = await this.detectCPMMPools(tokenMint);
       for (const pool of cpmmPools) {
-        if (!this.detectedPools.has(pool.toBase58())) {
+        if (!this.detectedPools.has(pool.pool.toBase58())) {
           newPools.push(pool);
-          this.detectedPools.add(pool.toBase58());
+          this.detectedPools.add(pool.pool.toBase58());
         }
       }
       
       // Detect V4 pools
       const v4Pools = await this.detectV4Pools(tokenMint);
       for (const pool of v4Pools) {
-        if (!this.detectedPools.has(pool.toBase58())) {
+        if (!this.detectedPools.has(pool.pool.toBase58())) {
           newPools.push(pool);
-          this.detectedPools.add(pool.toBase58());
+          this.detectedPools.add(pool.pool.toBase58());
         }
       }
       
       // Detect CLMM pools
       const clmmPools = await this.detectCLMMPools(tokenMint);
       for (const pool of clmmPools) {
-        if (!this.detectedPools.has(pool.toBase58())) {
+        if (!this.detectedPools.has(pool.pool.toBase58())) {
           newPools.push(pool);
-          this.detectedPools.add(pool.toBase58());
+          this.detectedPools.add(pool.pool.toBase58());
         }
       }
       
@@ -63,9 +73,9 @@ export class PoolDetector {
   /**
    * Detect Raydium CPMM pools
    */
-  private async detectCPMMPools(tokenMint: PublicKey): Promise<PublicKey[]> {
+  private async detectCPMMPools(tokenMint: PublicKey): Promise<DetectedPool[]> {
     try {
-      const pools: PublicKey[] = [];
+      const pools: DetectedPool[] = [];
       
       // Get all CPMM pool accounts
       const accounts = await this.connection.getProgramAccounts(
@@ -86,13 +96,20 @@ export class PoolDetector {
           // This is a simplified check - in production you'd decode the full state
           const data = account.account.data;
           
-          // Token A mint is typically at offset 72
-          const tokenAMint = new PublicKey(data.slice(72, 104));
-          // Token B mint is typically at offset 104  
-          const tokenBMint = new PublicKey(data.slice(104, 136));
+          // Token vaults are at offsets 72 and 104, mints at 168 and 200
+          const tokenAVault = new PublicKey(data.slice(72, 104));
+          const tokenBVault = new PublicKey(data.slice(104, 136));
+          const tokenAMint = new PublicKey(data.slice(168, 200));
+          const tokenBMint = new PublicKey(data.slice(200, 232));
           
           if (tokenAMint.equals(tokenMint) || tokenBMint.equals(tokenMint)) {
-            pools.push(account.pubkey);
+            const isA = tokenAMint.equals(tokenMint);
+            pools.push({
+              pool: account.pubkey,
+              dexProgram: this.RAYDIUM_CPMM_PROGRAM,
+              tokenVault: isA ? tokenAVault : tokenBVault,
+              quoteMint: isA ? tokenBMint : tokenAMint,
+            });
             this.logger.debug('Found CPMM pool', {
               pool: account.pubkey.toBase58(),
               tokenA: tokenAMint.toBase58(),
@@ -114,9 +131,9 @@ export class PoolDetector {
   /**
    * Detect Raydium V4 pools
    */
-  private async detectV4Pools(tokenMint: PublicKey): Promise<PublicKey[]> {
+  private async detectV4Pools(tokenMint: PublicKey): Promise<DetectedPool[]> {
     try {
-      const pools: PublicKey[] = [];
+      const pools: DetectedPool[] = [];
       
       // Get all V4 pool accounts
       const accounts = await this.connection.getProgramAccounts(
@@ -136,13 +153,22 @@ export class PoolDetector {
           // V4 pool data structure has token mints at specific offsets
           const data = account.account.data;
           
+          // Coin and PC vaults are at offsets 336 and 368
           // Coin mint is at offset 400
           // PC mint is at offset 432
+          const coinVault = new PublicKey(data.slice(336, 368));
+          const pcVault = new PublicKey(data.slice(368, 400));
           const coinMint = new PublicKey(data.slice(400, 432));
           const pcMint = new PublicKey(data.slice(432, 464));
           
           if (coinMint.equals(tokenMint) || pcMint.equals(tokenMint)) {
-            pools.push(account.pubkey);
+            const isCoin = coinMint.equals(tokenMint);
+            pools.push({
+              pool: account.pubkey,
+              dexProgram: this.RAYDIUM_LIQUIDITY_POOL_V4,
+              tokenVault: isCoin ? coinVault : pcVault,
+              quoteMint: isCoin ? pcMint : coinMint,
+            });
             this.logger.debug('Found V4 pool', {
               pool: account.pubkey.toBase58(),
               coinMint: coinMint.toBase58(),
@@ -164,9 +190,9 @@ export class PoolDetector {
   /**
    * Detect Raydium CLMM pools
    */
-  private async detectCLMMPools(tokenMint: PublicKey): Promise<PublicKey[]> {
+  private async detectCLMMPools(tokenMint: PublicKey): Promise<DetectedPool[]> {
     try {
-      const pools: PublicKey[] = [];
+      const pools: DetectedPool[] = [];
       
       // Get all CLMM pool accounts
       const accounts = await this.connection.getProgramAccounts(
@@ -186,13 +212,20 @@ export class PoolDetector {
           // CLMM pool data structure has token mints at specific offsets
           const data = account.account.data;
           
-          // Token mint 0 is at offset 72
-          // Token mint 1 is at offset 104
-          const mint0 = new PublicKey(data.slice(72, 104));
-          const mint1 = new PublicKey(data.slice(104, 136));
+          // Token mints are at offsets 73 and 105, vaults at 137 and 169
+          const mint0 = new PublicKey(data.slice(73, 105));
+          const mint1 = new PublicKey(data.slice(105, 137));
+          const vault0 = new PublicKey(data.slice(137, 169));
+          const vault1 = new PublicKey(data.slice(169, 201));
           
           if (mint0.equals(tokenMint) || mint1.equals(tokenMint)) {
-            pools.push(account.pubkey);
+            const is0 = mint0.equals(tokenMint);
+            pools.push({
+              pool: account.pubkey,
+              dexProgram: this.RAYDIUM_CLMM_PROGRAM,
+              tokenVault: is0 ? vault0 : vault1,
+              quoteMint: is0 ? mint1 : mint0,
+            });
             this.logger.debug('Found CLMM pool', {
               pool: account.pubkey.toBase58(),
               mint0: mint0.toBase58(),
@@ -4,40 +4,44 @@ use std::path::Path;
 use solana_sdk::signature::{Keypair, Signer};
 
 fn main() {
-    // Get the program keypair path from environment or use default
+    // Get the program keypair paths from environment or use defaults
     let keypair_path = env::var("VAULT_PROGRAM_KEYPAIR")
         .unwrap_or_else(|_| "../../keypairs/vault-program-keypair.json".to_string());
+    let dial_keypair_path = env::var("SMART_DIAL_PROGRAM_KEYPAIR")
+        .unwrap_or_else(|_| "../../keypairs/smart-dial-program-keypair.json".to_string());
     
-    // Read the keypair file
-    let keypair_path = Path::new(&keypair_path);
-    if keypair_path.exists() {
-        let keypair_data = fs::read_to_string(keypair_path)
-            .expect("Failed to read keypair file");
-        let keypair_bytes: Vec<u8> = serde_json::from_str(&keypair_data)
-            .expect("Failed to parse keypair JSON");
-        let keypair = Keypair::try_from(&keypair_bytes[..])
-            .expect("Failed to create keypair from bytes");
-        
-        // Write the program ID to a file that will be included
-        let out_dir = env::var("OUT_DIR").unwrap();
-        let dest_path = Path::new(&out_dir).join("program_id.rs");
-        fs::write(
-            dest_path,
-            format!(
-                r#"declare_id!("{}");"#,
-                keypair.pubkey().to_string()
-            ),
-        ).expect("Failed to write program ID");
-    } else {
+    // Write the program IDs to files that will be included
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(
+        Path::new(&out_dir).join("program_id.rs"),
+        format!(r#"declare_id!("{}");"#, program_id(Path::new(&keypair_path))),
+    ).expect("Failed to write program ID");
+    // The vault only reads Smart Dial accounts owned by this program
+    fs::write(
+        Path::new(&out_dir).join("smart_dial_id.rs"),
+        format!(
+            r#"pub const SMART_DIAL_ID: Pubkey = pubkey!("{}");"#,
+            program_id(Path::new(&dial_keypair_path))
+        ),
+    ).expect("Failed to write Smart Dial program ID");
+    
+    // Tell Cargo to rerun if keypairs change
+    println!("cargo:rerun-if-changed={}", keypair_path);
+    println!("cargo:rerun-if-changed={}", dial_keypair_path);
+}
+
+// Read the program ID from its keypair file
+fn program_id(keypair_path: &Path) -> String {
+    if !keypair_path.exists() {
         // Fallback for when keypair doesn't exist (like in IDL generation)
-        let out_dir = env::var("OUT_DIR").unwrap();
-        let dest_path = Path::new(&out_dir).join("program_id.rs");
-        fs::write(
-            dest_path,
-            r#"declare_id!("11111111111111111111111111111111");"#,
-        ).expect("Failed to write placeholder ID");
+        return "11111111111111111111111111111111".to_string();
     }
     
-    // Tell Cargo to rerun if keypair changes
-    println!("cargo:rerun-if-changed={}", keypair_path.display());
+    let keypair_data = fs::read_to_string(keypair_path)
+        .expect("Failed to read keypair file");
+    let keypair_bytes: Vec<u8> = serde_json::from_str(&keypair_data)
+        .expect("Failed to parse keypair JSON");
+    let keypair = Keypair::try_from(&keypair_bytes[..])
+        .expect("Failed to create keypair from bytes");
+    keypair.pubkey().to_string()
 }
\ No newline at end of file
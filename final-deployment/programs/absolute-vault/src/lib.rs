@@ -1,41 +1,98 @@
+// Anchor 0.31 emits a call to the deprecated AccountInfo::realloc in its IDL resize
+// instruction at the crate root, where no narrower allow reaches. Drop with Anchor 0.32.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::DEFAULT_MS_PER_SLOT;
+use anchor_lang::solana_program::epoch_schedule::EpochSchedule;
+use anchor_lang::solana_program::hash::hashv;
 use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use anchor_spl::token_2022::{self, Token2022};
-use anchor_spl::token_interface::{TokenAccount, TokenInterface, Mint};
+use anchor_spl::token_interface::{self, TokenAccount, TokenInterface, Mint};
 use spl_token_2022::{
     extension::{
         transfer_fee::instruction::{
             harvest_withheld_tokens_to_mint, 
+            set_transfer_fee,
             withdraw_withheld_tokens_from_accounts,
             withdraw_withheld_tokens_from_mint
         },
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig, MAX_FEE_BASIS_POINTS},
+        BaseStateWithExtensions,
         StateWithExtensions,
     },
-    state::Mint as MintState,
+    state::{Account as TokenAccountState, Mint as MintState},
 };
 
 // Program ID is dynamically generated from keypair at compile time
 include!(concat!(env!("OUT_DIR"), "/program_id.rs"));
+include!(concat!(env!("OUT_DIR"), "/smart_dial_id.rs"));
 
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const POOL_REGISTRY_SEED: &[u8] = b"pool_registry";
-pub const MAX_EXCLUSIONS: usize = 100;
+pub const HOLDER_TREASURY_SEED: &[u8] = b"holder_treasury";
+pub const PENDING_CONFIG_SEED: &[u8] = b"pending_config";
+pub const DISTRIBUTION_EPOCH_SEED: &[u8] = b"distribution_epoch";
+pub const EPOCH_ESCROW_SEED: &[u8] = b"epoch_escrow";
+pub const KEEPER_LOG_SEED: &[u8] = b"keeper_log";
+pub const DEX_ALLOWLIST_SEED: &[u8] = b"dex_allowlist";
+pub const EXCLUSION_SEED: &[u8] = b"exclusion";
+pub const FEE_HISTORY_SEED: &[u8] = b"fee_history";
+// Smart Dial basket layout, mirrors smart-dial's DIAL_STATE_VERSION, REWARD_BASKET_OFFSET and BASKET_ENTRY_LEN
+pub const DIAL_LAYOUT_VERSION: u8 = 1;
+pub const DIAL_BASKET_OFFSET: usize = 73;
+pub const DIAL_BASKET_ENTRY_LEN: usize = 67;
+pub const DIAL_MAX_BASKET: usize = 3;
+pub const MAX_FEE_HISTORY: usize = 52;
+pub const DEFAULT_FEE_BPS_CAP: u16 = 3_000; // 30%, the launch rate
+pub const MAX_FEE_STEPS: usize = 8;
+pub const MAX_LAUNCH_SKEW: i64 = 5 * 60; // 5 minutes
+pub const VAULT_STATE_VERSION: u8 = 1;
+pub const POOL_REGISTRY_VERSION: u8 = 1;
+pub const KEEPER_WORK_LOG_VERSION: u8 = 1;
+pub const MAX_DEX_PROGRAMS: usize = 8;
+pub const KEEPER_LOG_PAGE_SIZE: u64 = 32;
+pub const MAX_EPOCH_RECIPIENTS: u32 = 65_536; // 8 KiB claim bitmap
+pub const MAX_PROOF_LEN: usize = 24;
+pub const MERKLE_LEAF_PREFIX: u8 = 0;
+pub const MERKLE_NODE_PREFIX: u8 = 1;
 pub const MAX_POOLS: usize = 50;
+pub const MAX_POOL_REGISTRY_PAGES: u16 = 16; // Every page must fit in one transaction for the duplicate check
+pub const MAX_HARVEST_BATCH: usize = 20;
 pub const HARVEST_THRESHOLD: u64 = 500_000_000_000_000; // 500k MIKO with 9 decimals
+pub const DEFAULT_CONFIG_DELAY: i64 = 48 * 60 * 60; // 48 hours
+pub const DEFAULT_EMERGENCY_DELAY: i64 = 6 * 60 * 60; // 6 hours
+pub const MAX_CONFIG_DELAY: i64 = 30 * 24 * 60 * 60; // 30 days
+pub const MAX_ADMIN_SIGNERS: usize = 10;
+pub const DEFAULT_WITHDRAW_WINDOW: i64 = 24 * 60 * 60; // 24 hours
+pub const WITHDRAW_BUCKETS: usize = 3; // Half-window buckets, together they always cover the trailing window
+pub const DEFAULT_MAX_WITHDRAW_PER_WINDOW: u64 = 5_000_000_000_000_000; // 5M MIKO with 9 decimals
+pub const BPS_DENOMINATOR: u64 = 10_000;
 pub const OWNER_TAX_SHARE: u64 = 20; // 20% to owner
 pub const HOLDERS_TAX_SHARE: u64 = 80; // 80% to holders
+pub const KEEPER_TOP_UP_THRESHOLD: u64 = 50_000_000; // 0.05 SOL, below it the keeper may take an allowance
 
 #[program]
 pub mod absolute_vault {
     use super::*;
 
     /// Initialize vault with separate authority and keeper_authority
-    pub fn initialize(
-        ctx: Context<Initialize>,
+    /// Exclusion marker PDAs for authority, keeper, owner wallet, vault and this program are
+    /// passed as writable remaining accounts in that order, skipping repeated keys
+    pub fn initialize<'info>(
+        ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
         owner_wallet: Pubkey,
         keeper_authority: Pubkey,
         min_hold_amount: u64,
     ) -> Result<()> {
+        require_keys_neq!(
+            keeper_authority,
+            ctx.accounts.authority.key(),
+            VaultError::KeeperMatchesAuthority
+        );
+        
         let vault = &mut ctx.accounts.vault;
         
         vault.authority = ctx.accounts.authority.key();
@@ -43,119 +100,540 @@ pub mod absolute_vault {
         vault.owner_wallet = owner_wallet;
         vault.token_mint = ctx.accounts.token_mint.key();
         vault.min_hold_amount = min_hold_amount;
-        vault.reward_exclusions = vec![
-            ctx.accounts.authority.key(),
-            keeper_authority,
-            owner_wallet,
-            vault.key(),
-            ctx.accounts.vault_program.key(),
-        ];
+        vault.reward_exclusions = Vec::new();
         vault.harvest_threshold = HARVEST_THRESHOLD;
+        vault.owner_tax_share = OWNER_TAX_SHARE;
+        vault.holders_tax_share = HOLDERS_TAX_SHARE;
         vault.total_fees_harvested = 0;
-        vault.total_rewards_distributed = 0;
         vault.pending_withheld = 0;
         vault.last_harvest_time = 0;
         vault.last_distribution_time = 0;
         vault.launch_timestamp = 0;
+        vault.distribution_epoch_count = 0;
+        vault.reward_dial = Pubkey::default();
+        vault.pending_authority = Pubkey::default();
+        vault.pending_keeper_authority = Pubkey::default();
+        vault.config_delay = DEFAULT_CONFIG_DELAY;
+        vault.emergency_delay = DEFAULT_EMERGENCY_DELAY;
+        vault.config_change_count = 0;
+        vault.admin_signers = Vec::new();
+        vault.admin_threshold = 0;
+        vault.withdraw_window = DEFAULT_WITHDRAW_WINDOW;
+        vault.max_withdraw_per_window = DEFAULT_MAX_WITHDRAW_PER_WINDOW;
+        vault.max_withdraw_bps = BPS_DENOMINATOR as u16;
+        vault.window_start = 0;
+        vault.window_withdrawn = 0;
+        vault.withdraw_buckets = [0; WITHDRAW_BUCKETS];
+        vault.ledger = FeeLedger::default();
+        vault.keeper_log_count = 0;
+        vault.pool_registry_pages = 0;
+        vault.require_verified_pools = false;
+        vault.fee_bps_cap = DEFAULT_FEE_BPS_CAP;
+        vault.max_fee_cap = u64::MAX;
+        vault.transfer_fee = TransferFeeRates::default();
+        vault.fee_schedule = Vec::new();
+        vault.active_fee_step = None;
+        vault.pending_fee_step = None;
+        vault.require_launch_pool = false;
+        vault.version = VAULT_STATE_VERSION;
+        
+        // System exclusions start out as markers, not in the legacy list
+        let vault_key = vault.key();
+        let wallets = system_exclusions(vault, vault_key);
+        require!(
+            ctx.remaining_accounts.len() == wallets.len(),
+            VaultError::InvalidExclusionMarker
+        );
+        let now = Clock::get()?.unix_timestamp;
+        for (wallet, account) in wallets.iter().zip(ctx.remaining_accounts) {
+            let (expected, bump) = exclusion_marker_address(&vault_key, wallet);
+            require_keys_eq!(account.key(), expected, VaultError::InvalidExclusionMarker);
+            
+            let marker = ExclusionMarkerAccounts {
+                marker: account.clone(),
+                bump,
+                payer: ctx.accounts.payer.to_account_info(),
+                rent_recipient: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            };
+            create_exclusion_marker(&vault_key, &marker, *wallet, ExclusionReason::System, now)?;
+        }
+        vault.exclusion_count = wallets.len() as u64;
         
         msg!("Vault initialized");
         msg!("Authority: {}", vault.authority);
         msg!("Keeper Authority: {}", vault.keeper_authority);
         msg!("Owner Wallet: {}", vault.owner_wallet);
         
+        emit!(VaultInitialized {
+            vault: vault.key(),
+            token_mint: vault.token_mint,
+            authority: vault.authority,
+            keeper_authority: vault.keeper_authority,
+            owner_wallet: vault.owner_wallet,
+            min_hold_amount: vault.min_hold_amount,
+            harvest_threshold: vault.harvest_threshold,
+            exclusion_count: vault.exclusion_count,
+        });
+        
         Ok(())
     }
 
-    /// Initialize pool registry
-    pub fn initialize_pool_registry(ctx: Context<InitializePoolRegistry>) -> Result<()> {
-        let registry = &mut ctx.accounts.pool_registry;
+    /// Initialize the next pool registry page (permissionless)
+    /// Pages are created in order, page 0 keeps the original registry address
+    pub fn initialize_pool_registry(ctx: Context<InitializePoolRegistry>, page: u16) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
         
-        registry.vault = ctx.accounts.vault.key();
+        require!(
+            page == vault.pool_registry_pages && page < MAX_POOL_REGISTRY_PAGES,
+            VaultError::InvalidRegistryPage
+        );
+        
+        let registry = &mut ctx.accounts.pool_registry;
+        registry.vault = vault.key();
+        registry.page = page;
         registry.pools = Vec::new();
+        registry.version = POOL_REGISTRY_VERSION;
         
-        msg!("Pool registry initialized");
+        vault.pool_registry_pages = page
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        
+        msg!("Pool registry page {} initialized", page);
         Ok(())
     }
 
-    /// Set launch time (one-time only, permissionless)
-    pub fn set_launch_time(ctx: Context<SetLaunchTime>) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+    /// Set launch time (one-time only, authority or keeper)
+    /// `timestamp` defaults to now and must be within `MAX_LAUNCH_SKEW` of the clock.
+    /// A registered pool proves liquidity exists, it is required if `require_launch_pool` is set.
+    pub fn set_launch_time(ctx: Context<SetLaunchTime>, timestamp: Option<i64>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let launch_timestamp = timestamp.unwrap_or(now);
         
         require!(
-            vault.launch_timestamp == 0,
+            ctx.accounts.vault.launch_timestamp == 0,
             VaultError::LaunchTimeAlreadySet
         );
+        require!(
+            launch_timestamp > 0
+                && launch_timestamp.abs_diff(now) <= MAX_LAUNCH_SKEW as u64,
+            VaultError::InvalidLaunchTime
+        );
         
-        vault.launch_timestamp = Clock::get()?.unix_timestamp;
+        let pool = match (&ctx.accounts.pool_registry, &ctx.accounts.pool_state) {
+            (Some(registry), Some(pool_state)) => {
+                let entry = registry.pools
+                    .iter()
+                    .find(|entry| entry.pool == pool_state.key())
+                    .ok_or(VaultError::PoolNotRegistered)?;
+                // Legacy entries carry no DEX program, the pool only has to exist
+                require!(
+                    !pool_state.data_is_empty()
+                        && (entry.dex_program == Pubkey::default() || *pool_state.owner == entry.dex_program),
+                    VaultError::InvalidPoolAccount
+                );
+                Some(pool_state.key())
+            }
+            (None, None) => None,
+            _ => return err!(VaultError::MissingAccount),
+        };
+        require!(
+            pool.is_some() || !ctx.accounts.vault.require_launch_pool,
+            VaultError::LaunchPoolRequired
+        );
+        
+        let vault = &mut ctx.accounts.vault;
+        vault.launch_timestamp = launch_timestamp;
         
         msg!("Launch time set: {}", vault.launch_timestamp);
+        
+        emit!(LaunchTimeSet {
+            vault: vault.key(),
+            launch_timestamp: vault.launch_timestamp,
+            set_by: ctx.accounts.signer.key(),
+            pool,
+        });
+        
         Ok(())
     }
 
-    /// Update pool registry with detected pools (keeper only)
+    /// Update a pool registry page with detected pools (keeper only)
+    /// Every other registry page goes in remaining accounts, in page order, so a pool sits on one page only
     pub fn update_pool_registry(
         ctx: Context<UpdatePoolRegistry>,
-        pools_to_add: Vec<Pubkey>,
+        page: u16,
+        pools_to_add: Vec<PoolInfo>,
     ) -> Result<()> {
+        require!(
+            !ctx.accounts.vault.require_verified_pools,
+            VaultError::PoolVerificationRequired
+        );
+        let pools: Vec<Pubkey> = pools_to_add.iter().map(|info| info.pool).collect();
+        check_other_registry_pages(&ctx.accounts.vault, page, ctx.remaining_accounts, &pools)?;
+        
         let registry = &mut ctx.accounts.pool_registry;
+        let added_at = Clock::get()?.unix_timestamp;
+        let mut added = Vec::with_capacity(pools_to_add.len());
         
-        for pool in pools_to_add {
-            if !registry.pools.contains(&pool) && registry.pools.len() < MAX_POOLS {
-                registry.pools.push(pool);
-            }
+        for info in pools_to_add {
+            registry.add(info, added_at)?;
+            added.push(info.pool);
+        }
+        
+        msg!("Pool registry page {} updated. Total pools: {}", page, registry.pools.len());
+        
+        emit!(PoolRegistryUpdated {
+            vault: ctx.accounts.vault.key(),
+            page,
+            added,
+            removed: Vec::new(),
+            total_pools: registry.pools.len() as u32,
+        });
+        
+        Ok(())
+    }
+
+    /// Remove pools from a pool registry page (keeper only)
+    pub fn remove_pools(
+        ctx: Context<UpdatePoolRegistry>,
+        page: u16,
+        pools_to_remove: Vec<Pubkey>,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.pool_registry;
+        
+        for pool in pools_to_remove.iter() {
+            let position = registry.pools
+                .iter()
+                .position(|entry| entry.pool == *pool)
+                .ok_or(VaultError::PoolNotRegistered)?;
+            registry.pools.remove(position);
         }
         
-        msg!("Pool registry updated. Total pools: {}", registry.pools.len());
+        msg!("Removed {} pools from registry page {}. Total pools: {}", pools_to_remove.len(), page, registry.pools.len());
+        
+        emit!(PoolRegistryUpdated {
+            vault: ctx.accounts.vault.key(),
+            page,
+            added: Vec::new(),
+            removed: pools_to_remove,
+            total_pools: registry.pools.len() as u32,
+        });
+        
+        Ok(())
+    }
+
+    /// Register a pool after checking it on chain (keeper only)
+    /// The pool state must be owned by an allowlisted DEX program and trade `vault.token_mint`.
+    /// Other registry pages are passed as in `update_pool_registry`.
+    pub fn add_verified_pool(ctx: Context<AddVerifiedPool>, page: u16) -> Result<()> {
+        let pool_state = &ctx.accounts.pool_state;
+        check_other_registry_pages(&ctx.accounts.vault, page, ctx.remaining_accounts, &[pool_state.key()])?;
+        let dex = ctx.accounts.dex_allowlist
+            .find(pool_state.owner)
+            .ok_or(VaultError::DexNotAllowlisted)?;
+        
+        let data = pool_state.try_borrow_data()?;
+        let sides = dex.kind.decode_pool(&data)?;
+        drop(data);
+        
+        let token_mint = ctx.accounts.vault.token_mint;
+        let (token_vault, quote_mint) = match sides {
+            [(mint, vault), (quote, _)] if mint == token_mint => (vault, quote),
+            [(quote, _), (mint, vault)] if mint == token_mint => (vault, quote),
+            _ => return err!(VaultError::PoolMintMismatch),
+        };
+        
+        let info = PoolInfo {
+            pool: pool_state.key(),
+            dex_program: dex.program_id,
+            token_vault,
+            quote_mint,
+        };
+        let registry = &mut ctx.accounts.pool_registry;
+        registry.add(info, Clock::get()?.unix_timestamp)?;
+        
+        msg!("Verified {:?} pool {} with MIKO vault {}", dex.kind, info.pool, token_vault);
+        
+        emit!(PoolRegistryUpdated {
+            vault: ctx.accounts.vault.key(),
+            page,
+            added: vec![info.pool],
+            removed: Vec::new(),
+            total_pools: registry.pools.len() as u32,
+        });
+        
         Ok(())
     }
 
+    /// Create the DEX allowlist used for pool verification (permissionless, starts empty)
+    pub fn initialize_dex_allowlist(ctx: Context<InitializeDexAllowlist>) -> Result<()> {
+        let allowlist = &mut ctx.accounts.dex_allowlist;
+        
+        allowlist.vault = ctx.accounts.vault.key();
+        allowlist.programs = Vec::new();
+        
+        msg!("DEX allowlist initialized");
+        Ok(())
+    }
+
+    /// Add or remove an allowlisted DEX program (admin only)
+    pub fn manage_dex_allowlist(
+        ctx: Context<ManageDexAllowlist>,
+        action: AllowlistAction,
+        dex: DexProgram,
+    ) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        apply_dex_allowlist(&mut ctx.accounts.dex_allowlist, vault_key, action, dex)
+    }
+
     /// Harvest fees from token accounts to mint (keeper only)
+    /// Source token accounts are passed as writable remaining accounts
     pub fn harvest_fees<'info>(
-        ctx: Context<'_, '_, '_, 'info, HarvestFees<'info>>, 
-        accounts: Vec<Pubkey>
+        ctx: Context<'_, '_, '_, 'info, HarvestFees<'info>>,
     ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
         
-        require!(
-            accounts.len() > 0 && accounts.len() <= 20,
-            VaultError::InvalidBatchSize
-        );
+        let batch = collect_withheld_sources(ctx.remaining_accounts, &vault.token_mint)?;
         
-        // Build harvest instruction
-        let seeds = &[
-            VAULT_SEED,
-            vault.token_mint.as_ref(),
-            &[ctx.bumps.vault]
-        ];
-        let signer_seeds = &[&seeds[..]];
+        if !batch.sources.is_empty() {
+            let seeds = &[
+                VAULT_SEED,
+                vault.token_mint.as_ref(),
+                &[ctx.bumps.vault]
+            ];
+            let signer_seeds = &[&seeds[..]];
+            
+            let source_keys: Vec<&Pubkey> = batch.sources.iter().map(|a| a.key).collect();
+            
+            let ix = harvest_withheld_tokens_to_mint(
+                &ctx.accounts.token_program.key(),
+                &ctx.accounts.token_mint.key(),
+                &source_keys,
+            )?;
+            
+            let mut account_infos = vec![ctx.accounts.token_mint.to_account_info()];
+            account_infos.extend(batch.sources.iter().cloned());
+            
+            invoke_signed(
+                &ix,
+                &account_infos,
+                signer_seeds,
+            )?;
+        }
+        
+        // Harvesting moves fees from accounts to the mint without changing the pending total,
+        // unless the mint now holds more than was counted
+        let mint_withheld = read_mint_withheld(&ctx.accounts.token_mint)?;
+        vault.pending_withheld = vault.pending_withheld.max(mint_withheld);
+        vault.last_harvest_time = Clock::get()?.unix_timestamp;
         
-        let account_refs: Vec<&Pubkey> = accounts.iter().collect();
+        msg!("Harvested {} from {} accounts ({} skipped)", batch.total, batch.harvested.len(), batch.skipped.len());
         
-        let ix = harvest_withheld_tokens_to_mint(
-            &ctx.accounts.token_program.key(),
-            &ctx.accounts.token_mint.key(),
-            &account_refs,
-        )?;
+        emit!(FeesHarvested {
+            vault: vault.key(),
+            token_mint: vault.token_mint,
+            harvested: batch.harvested,
+            skipped: batch.skipped,
+            total: batch.total,
+            pending_withheld: vault.pending_withheld,
+        });
+        
+        Ok(())
+    }
+
+    /// Create the transfer fee rate history (permissionless)
+    pub fn initialize_fee_history(ctx: Context<InitializeFeeHistory>) -> Result<()> {
+        let history = &mut ctx.accounts.fee_history;
         
-        // Collect all account infos
-        let mut account_infos = vec![ctx.accounts.token_mint.to_account_info()];
-        account_infos.extend(ctx.remaining_accounts.iter().cloned());
+        history.vault = ctx.accounts.vault.key();
+        history.changes = Vec::new();
         
-        invoke_signed(
-            &ix,
-            &account_infos,
-            signer_seeds,
+        msg!("Fee history initialized");
+        Ok(())
+    }
+
+    /// Set the Token-2022 transfer fee, signed by the vault PDA (admin only)
+    /// The new rate activates two epochs later, until then it is reported as pending
+    pub fn set_transfer_fee(
+        ctx: Context<SetTransferFee>,
+        basis_points: u16,
+        maximum_fee: u64,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        apply_transfer_fee(
+            &mut accounts.vault,
+            ctx.bumps.vault,
+            &accounts.token_mint,
+            &accounts.token_program,
+            &mut accounts.fee_history,
+            basis_points,
+            maximum_fee,
+        )
+    }
+
+    /// Configure the launch tax schedule (admin only, before launch)
+    pub fn set_fee_schedule(ctx: Context<SetFeeSchedule>, steps: Vec<FeeStep>) -> Result<()> {
+        apply_fee_schedule(&mut ctx.accounts.vault, steps)
+    }
+
+    /// Move the fee schedule forward (permissionless)
+    /// A step set on the mint becomes active at its effective epoch, two epochs later, and the
+    /// next due step is only set once no change is pending, so none is ever replaced unapplied.
+    /// The launch step must already be the mint's rate at launch, it is then marked active at once.
+    /// Token-2022 cannot apply it any sooner, so a launch step missing from the mint is rejected.
+    pub fn crank_fee_schedule(ctx: Context<CrankFeeSchedule>) -> Result<()> {
+        require!(ctx.accounts.vault.launch_timestamp > 0, VaultError::LaunchTimeNotSet);
+        
+        let clock = Clock::get()?;
+        let vault_key = ctx.accounts.vault.key();
+        let rates = read_transfer_fee(&ctx.accounts.token_mint, clock.epoch)?;
+        let vault = &mut ctx.accounts.vault;
+        vault.transfer_fee = rates;
+        
+        // A step set by an earlier crank counts as active once its epoch has started
+        let mut promoted = false;
+        if let Some(index) = vault.pending_fee_step {
+            require!(rates.pending_epoch == 0, VaultError::FeeStepPending);
+            vault.pending_fee_step = None;
+            vault.active_fee_step = Some(index);
+            promoted = true;
+            
+            let step = vault.fee_schedule[index as usize];
+            msg!("Fee schedule step {} active from epoch {}", index, clock.epoch);
+            
+            emit!(FeeStepActivated {
+                vault: vault_key,
+                step: index,
+                basis_points: step.basis_points,
+                maximum_fee: step.maximum_fee,
+                epoch: clock.epoch,
+            });
+        }
+        
+        let elapsed = clock
+            .unix_timestamp
+            .checked_sub(vault.launch_timestamp)
+            .ok_or(VaultError::MathOverflow)?;
+        let due = vault.fee_schedule
+            .iter()
+            .rposition(|step| step.starts_after <= elapsed)
+            .map(|index| index as u8);
+        let index = match due {
+            Some(index) if vault.active_fee_step != Some(index) => index,
+            _ if promoted => return Ok(()),
+            Some(_) => return err!(VaultError::FeeStepAlreadyActive),
+            None => return err!(VaultError::NoFeeStepDue),
+        };
+        let step = vault.fee_schedule[index as usize];
+        
+        // A rate already in effect on the mint needs no CPI and no wait
+        if rates.active_basis_points == step.basis_points && rates.active_maximum_fee == step.maximum_fee {
+            vault.active_fee_step = Some(index);
+            
+            msg!("Fee schedule step {} already in effect on the mint", index);
+            
+            emit!(FeeStepActivated {
+                vault: vault_key,
+                step: index,
+                basis_points: step.basis_points,
+                maximum_fee: step.maximum_fee,
+                epoch: clock.epoch,
+            });
+            return Ok(());
+        }
+        
+        // Set on the mint now, the launch rate would only apply two epochs after launch
+        require!(index > 0, VaultError::LaunchFeeNotOnMint);
+        
+        // Setting a rate replaces a newer one not yet in effect, wait for it instead
+        require!(rates.pending_epoch == 0, VaultError::FeeChangePending);
+        
+        let accounts = ctx.accounts;
+        apply_transfer_fee(
+            &mut accounts.vault,
+            ctx.bumps.vault,
+            &accounts.token_mint,
+            &accounts.token_program,
+            &mut accounts.fee_history,
+            step.basis_points,
+            step.maximum_fee,
         )?;
+        accounts.vault.pending_fee_step = Some(index);
+        let effective_epoch = accounts.vault.transfer_fee.pending_epoch;
         
-        vault.last_harvest_time = Clock::get()?.unix_timestamp;
+        msg!("Fee schedule step {} set {}s after launch, active from epoch {}", index, elapsed, effective_epoch);
+        
+        emit!(FeeStepApplied {
+            vault: vault_key,
+            step: index,
+            basis_points: step.basis_points,
+            maximum_fee: step.maximum_fee,
+            elapsed,
+            effective_epoch,
+        });
+        
+        Ok(())
+    }
+
+    /// Refresh the active and pending transfer fee from the mint (permissionless)
+    pub fn sync_transfer_fee(ctx: Context<SyncTransferFee>) -> Result<()> {
+        let rates = read_transfer_fee(&ctx.accounts.token_mint, Clock::get()?.epoch)?;
+        ctx.accounts.vault.transfer_fee = rates;
+        
+        msg!("Transfer fee: {} bps active, {} bps pending", rates.active_basis_points, rates.pending_basis_points);
+        
+        Ok(())
+    }
+
+    /// Recount pending_withheld from the mint and the token accounts holding withheld fees (keeper only)
+    /// Token accounts are passed as remaining accounts, each at most once
+    pub fn sync_pending_withheld<'info>(
+        ctx: Context<'_, '_, '_, 'info, SyncPendingWithheld<'info>>,
+    ) -> Result<()> {
+        let token_mint = ctx.accounts.vault.token_mint;
+        let mut pending = read_mint_withheld(&ctx.accounts.token_mint)?;
         
-        msg!("Harvested fees from {} accounts", accounts.len());
+        require_unique_accounts(ctx.remaining_accounts)?;
+        
+        for account in ctx.remaining_accounts.iter() {
+            pending = pending
+                .checked_add(read_account_withheld(account, &token_mint)?)
+                .ok_or(VaultError::MathOverflow)?;
+        }
+        
+        ctx.accounts.vault.pending_withheld = pending;
+        
+        msg!("Pending withheld: {}", pending);
         
         Ok(())
     }
 
     /// Withdraw fees from mint to vault PDA (keeper only)
+    /// Requires the mint to hold at least `harvest_threshold`, unless the authority co-signs
     pub fn withdraw_fees_from_mint(ctx: Context<WithdrawFeesFromMint>) -> Result<()> {
+        let withheld_on_mint = read_mint_withheld(&ctx.accounts.token_mint)?;
+        
+        match &ctx.accounts.authority {
+            Some(authority) => {
+                require!(
+                    !ctx.accounts.vault.multisig_enabled(),
+                    VaultError::MultisigRequired
+                );
+                require_keys_eq!(
+                    authority.key(),
+                    ctx.accounts.vault.authority,
+                    VaultError::Unauthorized
+                );
+                msg!("Harvest threshold overridden by authority");
+            }
+            None => {
+                require!(
+                    withheld_on_mint >= ctx.accounts.vault.harvest_threshold,
+                    VaultError::BelowHarvestThreshold
+                );
+            }
+        }
+        
         // Get current vault balance before withdrawal
         let vault_balance_before = ctx.accounts.vault_token_account.amount;
         
@@ -190,27 +668,200 @@ pub mod absolute_vault {
         // Reload account to get updated balance
         ctx.accounts.vault_token_account.reload()?;
         let vault_balance_after = ctx.accounts.vault_token_account.amount;
+        let threshold_overridden = ctx.accounts.authority.is_some();
         
         // Calculate withdrawn amount
-        let withdrawn_amount = vault_balance_after.saturating_sub(vault_balance_before);
+        let withdrawn_amount = vault_balance_after
+            .checked_sub(vault_balance_before)
+            .ok_or(VaultError::MathOverflow)?;
         
         // Update vault state
         let vault = &mut ctx.accounts.vault;
-        vault.total_fees_harvested = vault.total_fees_harvested.saturating_add(withdrawn_amount);
+        vault.total_fees_harvested = vault.total_fees_harvested
+            .checked_add(withdrawn_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        ledger_credit(&mut vault.ledger.harvested, withdrawn_amount)?;
+        vault.reconcile_ledger(Some(vault_balance_after), None, None)?;
         vault.last_harvest_amount = withdrawn_amount;
         vault.last_harvest_time = Clock::get()?.unix_timestamp;
+        // Fees withheld on token accounts are still pending, a stale-low count floors at zero
+        vault.pending_withheld = vault.pending_withheld.saturating_sub(withdrawn_amount);
         
         msg!("Withdrew {} fees from mint to vault", withdrawn_amount);
         
+        emit!(FeesWithdrawnFromMint {
+            vault: vault.key(),
+            token_mint: vault.token_mint,
+            vault_token_account: ctx.accounts.vault_token_account.key(),
+            amount: withdrawn_amount,
+            threshold_overridden,
+            total_fees_harvested: vault.total_fees_harvested,
+            pending_withheld: vault.pending_withheld,
+            ledger: vault.ledger,
+        });
+        
+        Ok(())
+    }
+
+    /// Split harvested fees between owner and holder treasury (keeper only)
+    /// Owner share goes straight to the owner wallet, holder share stays vault-owned
+    /// While the keeper is low on SOL it may take up to the owner share as an allowance into its
+    /// custody, swapped to SOL and logged as `KeeperTopUp`.
+    pub fn split_harvested_fees(
+        ctx: Context<SplitHarvestedFees>,
+        amount: u64,
+        keeper_allowance: u64,
+    ) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        
+        require!(amount > 0, VaultError::InvalidAmount);
+        require!(
+            vault.owner_tax_share.checked_add(vault.holders_tax_share) == Some(100),
+            VaultError::InvalidDistributionSplit
+        );
+        
+        let owner_amount = (amount as u128)
+            .checked_mul(vault.owner_tax_share as u128)
+            .and_then(|v| v.checked_div(100))
+            .ok_or(VaultError::MathOverflow)? as u64;
+        let holders_amount = amount
+            .checked_sub(owner_amount)
+            .ok_or(VaultError::MathOverflow)?;
+        check_keeper_allowance(owner_amount, keeper_allowance, ctx.accounts.keeper_authority.lamports())?;
+        
+        let token_mint_key = vault.token_mint;
+        let seeds = &[
+            VAULT_SEED,
+            token_mint_key.as_ref(),
+            &[ctx.bumps.vault]
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let decimals = ctx.accounts.token_mint.decimals;
+        
+        // Allocate out of the harvested bucket before paying anything out
+        let vault = &mut ctx.accounts.vault;
+        ledger_debit(&mut vault.ledger.harvested, amount)?;
+        ledger_credit(&mut vault.ledger.owed_to_owner, owner_amount)?;
+        
+        if keeper_allowance > 0 {
+            let keeper_token_account = ctx.accounts.keeper_token_account
+                .as_mut()
+                .ok_or(VaultError::MissingAccount)?;
+            let custody_before = keeper_token_account.amount;
+            
+            token_2022::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_2022::TransferChecked {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: keeper_token_account.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                keeper_allowance,
+                decimals,
+            )?;
+            
+            // Keeper custody counts what actually arrived, net of the transfer fee
+            keeper_token_account.reload()?;
+            let received = keeper_token_account.amount
+                .checked_sub(custody_before)
+                .ok_or(VaultError::MathOverflow)?;
+            let vault = &mut ctx.accounts.vault;
+            ledger_debit(&mut vault.ledger.owed_to_owner, keeper_allowance)?;
+            ledger_credit(&mut vault.ledger.keeper_custody, received)?;
+        }
+        
+        let owner_payout = owner_amount
+            .checked_sub(keeper_allowance)
+            .ok_or(VaultError::MathOverflow)?;
+        if owner_payout > 0 {
+            token_2022::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_2022::TransferChecked {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.owner_token_account.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                owner_payout,
+                decimals,
+            )?;
+            
+            let vault = &mut ctx.accounts.vault;
+            ledger_debit(&mut vault.ledger.owed_to_owner, owner_payout)?;
+            ledger_credit(&mut vault.ledger.paid_out, owner_payout)?;
+        }
+        
+        if holders_amount > 0 {
+            let treasury_before = ctx.accounts.holder_treasury.amount;
+            
+            token_2022::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_2022::TransferChecked {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        mint: ctx.accounts.token_mint.to_account_info(),
+                        to: ctx.accounts.holder_treasury.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                holders_amount,
+                decimals,
+            )?;
+            
+            // Count what the treasury received net of the transfer fee
+            ctx.accounts.holder_treasury.reload()?;
+            let received = ctx.accounts.holder_treasury.amount
+                .checked_sub(treasury_before)
+                .ok_or(VaultError::MathOverflow)?;
+            let vault = &mut ctx.accounts.vault;
+            ledger_credit(&mut vault.ledger.owed_to_holders, received)?;
+        }
+        
+        ctx.accounts.vault_token_account.reload()?;
+        ctx.accounts.vault.reconcile_ledger(
+            Some(ctx.accounts.vault_token_account.amount),
+            Some(ctx.accounts.holder_treasury.amount),
+            ctx.accounts.keeper_token_account.as_ref().map(|account| account.amount),
+        )?;
+        
+        msg!(
+            "Split {} fees: {} to owner, {} to keeper, {} to holder treasury",
+            amount, owner_payout, keeper_allowance, holders_amount
+        );
+        
+        emit!(HarvestedFeesSplit {
+            vault: ctx.accounts.vault.key(),
+            owner_token_account: ctx.accounts.owner_token_account.key(),
+            holder_treasury: ctx.accounts.holder_treasury.key(),
+            amount,
+            owner_amount: owner_payout,
+            keeper_allowance,
+            holders_amount,
+            ledger: ctx.accounts.vault.ledger,
+        });
+        
         Ok(())
     }
 
-    /// Withdraw harvested fees to keeper for processing (keeper only)
-    /// Keeper handles all swaps and distributions according to tax flow rules
+    /// Withdraw the holder share from the treasury to keeper for processing (keeper only)
+    /// Keeper swaps it to the reward token and distributes according to tax flow rules.
+    /// Limited per rolling window and to a share of fees split but not yet withdrawn.
+    /// The keeper account must still hold its unlogged custody, so spend is logged before the next withdrawal.
     pub fn withdraw_harvested_fees(
         ctx: Context<WithdrawHarvestedFees>,
         amount: u64,
     ) -> Result<()> {
+        require!(amount > 0, VaultError::InvalidAmount);
+        ctx.accounts.vault.record_withdrawal(amount, Clock::get()?.unix_timestamp)?;
+        
         let vault = &ctx.accounts.vault;
         
         let seeds = &[
@@ -227,12 +878,14 @@ pub mod absolute_vault {
         let decimals = mint_info.base.decimals;
         drop(mint_data_borrowed);
         
-        // Transfer harvested fees to keeper
+        let custody_before = ctx.accounts.keeper_token_account.amount;
+        
+        // Transfer holder share to keeper
         token_2022::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 token_2022::TransferChecked {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    from: ctx.accounts.holder_treasury.to_account_info(),
                     mint: ctx.accounts.token_mint.to_account_info(),
                     to: ctx.accounts.keeper_token_account.to_account_info(),
                     authority: ctx.accounts.vault.to_account_info(),
@@ -243,487 +896,3500 @@ pub mod absolute_vault {
             decimals,
         )?;
         
+        // Keeper custody counts what actually arrived, net of the transfer fee
+        ctx.accounts.keeper_token_account.reload()?;
+        ctx.accounts.holder_treasury.reload()?;
+        let received = ctx.accounts.keeper_token_account.amount
+            .checked_sub(custody_before)
+            .ok_or(VaultError::MathOverflow)?;
+        
         // Update vault state
         let vault = &mut ctx.accounts.vault;
+        ledger_credit(&mut vault.ledger.keeper_custody, received)?;
+        vault.reconcile_ledger(
+            None,
+            Some(ctx.accounts.holder_treasury.amount),
+            Some(ctx.accounts.keeper_token_account.amount),
+        )?;
         vault.last_distribution_time = Clock::get()?.unix_timestamp;
-        vault.total_rewards_distributed = vault.total_rewards_distributed
-            .saturating_add(amount);
         
         msg!("Withdrew {} tokens to keeper for processing", amount);
+        msg!("Window withdrawn: {}/{}", vault.window_withdrawn, vault.max_withdraw_per_window);
+        
+        emit!(FeesWithdrawnToKeeper {
+            vault: vault.key(),
+            keeper_token_account: ctx.accounts.keeper_token_account.key(),
+            amount,
+            received,
+            window_start: vault.window_start,
+            window_withdrawn: vault.window_withdrawn,
+            ledger: vault.ledger,
+        });
         
         Ok(())
     }
     
-    /// Log keeper work on-chain (keeper only)
-    /// Records swap and distribution activities for transparency
-    pub fn log_keeper_work(
-        ctx: Context<LogKeeperWork>,
-        work_type: KeeperWorkType,
-        amount: u64,
-        details: String,
+    /// Open a holder distribution epoch and escrow its rewards (keeper only)
+    /// Holders claim their share with a merkle proof against `merkle_root`
+    pub fn create_distribution_epoch(
+        ctx: Context<CreateDistributionEpoch>,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        recipient_count: u32,
     ) -> Result<()> {
-        let log = &mut ctx.accounts.keeper_work_log;
-        
-        // Initialize if new
-        if log.vault == Pubkey::default() {
-            log.vault = ctx.accounts.vault.key();
-            log.entries = Vec::new();
-        }
+        require!(total_amount > 0, VaultError::InvalidAmount);
+        require!(
+            recipient_count > 0 && recipient_count <= MAX_EPOCH_RECIPIENTS,
+            VaultError::InvalidRecipientCount
+        );
         
-        // Add new entry (keep last 50 entries)
-        log.entries.push(KeeperWorkEntry {
-            timestamp: Clock::get()?.unix_timestamp,
-            work_type,
-            amount,
-            details: details.chars().take(100).collect(), // Limit details to 100 chars
-        });
+        // Rewards must be paid in a token of the basket currently selected by Smart Dial
+        let dial_basket = read_dial_reward_basket(&ctx.accounts.reward_dial)?;
+        require!(
+            dial_basket.contains(&ctx.accounts.reward_mint.key()),
+            VaultError::RewardMintMismatch
+        );
         
-        if log.entries.len() > 50 {
-            log.entries.remove(0);
-        }
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.keeper_reward_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.epoch_escrow.to_account_info(),
+                    authority: ctx.accounts.keeper_authority.to_account_info(),
+                },
+            ),
+            total_amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
         
-        msg!("Logged keeper work: {:?}", work_type);
+        // Escrow must actually hold what the merkle tree promises
+        ctx.accounts.epoch_escrow.reload()?;
+        require!(
+            ctx.accounts.epoch_escrow.amount >= total_amount,
+            VaultError::EscrowUnderfunded
+        );
         
-        Ok(())
-    }
-
-    /// Manage reward exclusions (admin only)
-    pub fn manage_exclusions(
-        ctx: Context<ManageExclusions>,
-        action: ExclusionAction,
-        wallet: Pubkey,
-    ) -> Result<()> {
         let vault = &mut ctx.accounts.vault;
+        let epoch = &mut ctx.accounts.distribution_epoch;
         
-        match action {
-            ExclusionAction::Add => {
-                require!(
-                    vault.reward_exclusions.len() < MAX_EXCLUSIONS,
-                    VaultError::ExclusionListFull
-                );
-                require!(
-                    !vault.reward_exclusions.contains(&wallet),
-                    VaultError::AlreadyExcluded
-                );
-                vault.reward_exclusions.push(wallet);
-                msg!("Added {} to reward exclusions", wallet);
-            }
-            ExclusionAction::Remove => {
-                vault.reward_exclusions.retain(|&x| x != wallet);
-                msg!("Removed {} from reward exclusions", wallet);
-            }
-        }
+        epoch.vault = vault.key();
+        epoch.epoch = vault.distribution_epoch_count;
+        epoch.reward_mint = ctx.accounts.reward_mint.key();
+        epoch.merkle_root = merkle_root;
+        epoch.total_amount = total_amount;
+        epoch.claimed_amount = 0;
+        epoch.recipient_count = recipient_count;
+        epoch.created_at = Clock::get()?.unix_timestamp;
+        epoch.claimed_bitmap = vec![0u8; DistributionEpoch::bitmap_len(recipient_count)];
         
-        Ok(())
-    }
-
-    /// Update vault configuration (admin only)
-    pub fn update_config(
-        ctx: Context<UpdateConfig>,
-        new_owner_wallet: Option<Pubkey>,
-        new_min_hold_amount: Option<u64>,
-        new_harvest_threshold: Option<u64>,
-        new_authority: Option<Pubkey>,
-        new_keeper_authority: Option<Pubkey>,
-    ) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+        vault.distribution_epoch_count = vault.distribution_epoch_count
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
         
-        if let Some(owner) = new_owner_wallet {
-            vault.owner_wallet = owner;
-        }
-        if let Some(min_hold) = new_min_hold_amount {
-            vault.min_hold_amount = min_hold;
-        }
-        if let Some(threshold) = new_harvest_threshold {
-            vault.harvest_threshold = threshold;
-        }
-        if let Some(authority) = new_authority {
-            vault.authority = authority;
-        }
-        if let Some(keeper) = new_keeper_authority {
-            vault.keeper_authority = keeper;
-        }
+        emit!(DistributionEpochCreated {
+            vault: epoch.vault,
+            epoch: epoch.epoch,
+            reward_mint: epoch.reward_mint,
+            merkle_root,
+            total_amount,
+            recipient_count,
+        });
+        
+        msg!("Distribution epoch {} created: {} tokens for {} holders", epoch.epoch, total_amount, recipient_count);
         
-        msg!("Vault configuration updated");
         Ok(())
     }
 
-    /// Emergency withdraw from vault (admin only)
-    pub fn emergency_withdraw_vault(
-        ctx: Context<EmergencyWithdraw>,
+    /// Claim holder rewards from a distribution epoch (permissionless, claimant signs)
+    pub fn claim(
+        ctx: Context<Claim>,
+        index: u32,
         amount: u64,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+        let epoch = &mut ctx.accounts.distribution_epoch;
+        let claimant = ctx.accounts.claimant.key();
+        
+        require!(index < epoch.recipient_count, VaultError::InvalidClaimIndex);
+        require!(proof.len() <= MAX_PROOF_LEN, VaultError::InvalidMerkleProof);
+        require!(!epoch.is_claimed(index), VaultError::AlreadyClaimed);
+        
+        let leaf = hashv(&[
+            &[MERKLE_LEAF_PREFIX],
+            &index.to_le_bytes(),
+            claimant.as_ref(),
+            &amount.to_le_bytes(),
+        ]).to_bytes();
+        require!(
+            verify_merkle_proof(&proof, epoch.merkle_root, leaf),
+            VaultError::InvalidMerkleProof
+        );
+        
+        let claimed_amount = epoch.claimed_amount
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            claimed_amount <= epoch.total_amount,
+            VaultError::EscrowUnderfunded
+        );
+        
+        epoch.set_claimed(index);
+        epoch.claimed_amount = claimed_amount;
+        let epoch_number = epoch.epoch;
         
         let seeds = &[
             VAULT_SEED,
-            vault.token_mint.as_ref(),
+            ctx.accounts.vault.token_mint.as_ref(),
             &[ctx.bumps.vault]
         ];
         let signer_seeds = &[&seeds[..]];
         
-        // Get mint decimals
-        let mint_data = ctx.accounts.token_mint.to_account_info();
-        let mint_data_borrowed = mint_data.try_borrow_data()?;
-        let mint_info = StateWithExtensions::<MintState>::unpack(&mint_data_borrowed)?;
-        let decimals = mint_info.base.decimals;
-        drop(mint_data_borrowed);
-        
-        token_2022::transfer_checked(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                token_2022::TransferChecked {
-                    from: ctx.accounts.vault_token_account.to_account_info(),
-                    mint: ctx.accounts.token_mint.to_account_info(),
-                    to: ctx.accounts.destination_token_account.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.epoch_escrow.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.claimant_token_account.to_account_info(),
                     authority: ctx.accounts.vault.to_account_info(),
                 },
                 signer_seeds,
             ),
             amount,
-            decimals,
+            ctx.accounts.reward_mint.decimals,
         )?;
         
-        msg!("Emergency withdrawal: {} tokens", amount);
+        emit!(RewardClaimed {
+            vault: ctx.accounts.vault.key(),
+            epoch: epoch_number,
+            claimant,
+            index,
+            amount,
+        });
+        
+        msg!("Claimed {} from epoch {} for {}", amount, epoch_number, claimant);
         
         Ok(())
     }
 
-    /// Emergency withdraw withheld from mint (admin only)
-    pub fn emergency_withdraw_withheld<'info>(
-        ctx: Context<'_, '_, '_, 'info, EmergencyWithdrawWithheld<'info>>,
-        accounts: Vec<Pubkey>,
+    /// Log keeper work on-chain (keeper only)
+    /// Entries are appended to the page holding the vault's next sequence number.
+    /// Distributions and top-ups spend MIKO out of keeper custody and may not exceed it.
+    pub fn log_keeper_work(
+        ctx: Context<LogKeeperWork>,
+        work: KeeperWork,
     ) -> Result<()> {
-        let vault = &ctx.accounts.vault;
+        let vault = &mut ctx.accounts.vault;
+        let page = &mut ctx.accounts.keeper_work_log_page;
+        let sequence = vault.keeper_log_count;
+        let page_index = vault.keeper_log_page();
+        
+        // Initialize if new
+        if page.vault == Pubkey::default() {
+            page.vault = vault.key();
+            page.page = page_index;
+            page.entries = Vec::new();
+        }
         
+        let spent = work.custody_spent();
         require!(
-            accounts.len() > 0 && accounts.len() <= 20,
-            VaultError::InvalidBatchSize
+            spent <= vault.ledger.keeper_custody,
+            VaultError::ExceedsKeeperCustody
         );
+        ledger_debit(&mut vault.ledger.keeper_custody, spent)?;
+        ledger_credit(&mut vault.ledger.paid_out, spent)?;
         
-        let seeds = &[
-            VAULT_SEED,
-            vault.token_mint.as_ref(),
-            &[ctx.bumps.vault]
-        ];
-        let signer_seeds = &[&seeds[..]];
+        let timestamp = Clock::get()?.unix_timestamp;
+        page.entries.push(KeeperWorkEntry {
+            sequence,
+            timestamp,
+            work,
+        });
+        vault.keeper_log_count = sequence
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
         
-        let account_refs: Vec<&Pubkey> = accounts.iter().collect();
+        msg!("Logged keeper work #{}: {:?}", sequence, work.work_type());
         
-        let ix = withdraw_withheld_tokens_from_accounts(
-            &ctx.accounts.token_program.key(),
-            &ctx.accounts.token_mint.key(),
-            &ctx.accounts.destination_token_account.key(),
-            &vault.key(),
-            &account_refs,
-            &[],
-        )?;
+        emit!(KeeperWorkLogged {
+            vault: vault.key(),
+            sequence,
+            page: page_index,
+            timestamp,
+            work,
+            keeper_custody: vault.ledger.keeper_custody,
+        });
         
-        let mut account_infos = vec![
-            ctx.accounts.token_mint.to_account_info(),
-            ctx.accounts.destination_token_account.to_account_info(),
-            ctx.accounts.vault.to_account_info(),
-        ];
-        account_infos.extend(ctx.remaining_accounts.iter().cloned());
+        Ok(())
+    }
+
+    /// Manage reward exclusions (admin only)
+    /// Adding creates the wallet's exclusion marker, removing closes it and refunds the authority
+    pub fn manage_exclusions(
+        ctx: Context<ManageExclusions>,
+        action: ExclusionAction,
+        wallet: Pubkey,
+        reason: ExclusionReason,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        let marker = ExclusionMarkerAccounts {
+            marker: accounts.exclusion_marker.to_account_info(),
+            bump: ctx.bumps.exclusion_marker,
+            payer: accounts.authority.to_account_info(),
+            rent_recipient: accounts.authority.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+        };
+        apply_exclusion(&mut accounts.vault, &marker, action, wallet, reason)
+    }
+
+    /// Move legacy `reward_exclusions` entries into exclusion markers (permissionless)
+    /// Marker PDAs for the first entries of the list are passed as writable remaining accounts
+    pub fn migrate_exclusions<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateExclusions<'info>>,
+    ) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let count = ctx.remaining_accounts.len().min(ctx.accounts.vault.reward_exclusions.len());
+        let now = Clock::get()?.unix_timestamp;
+        let mut created = 0u32;
         
-        invoke_signed(
-            &ix,
-            &account_infos,
-            signer_seeds,
-        )?;
+        for (i, account) in ctx.remaining_accounts[..count].iter().enumerate() {
+            let vault = &ctx.accounts.vault;
+            let wallet = vault.reward_exclusions[i];
+            let (expected, bump) = exclusion_marker_address(&vault_key, &wallet);
+            require_keys_eq!(account.key(), expected, VaultError::InvalidExclusionMarker);
+            
+            if marker_exists(account) {
+                continue;
+            }
+            
+            let reason = if system_exclusions(vault, vault_key).contains(&wallet) {
+                ExclusionReason::System
+            } else {
+                ExclusionReason::Manual
+            };
+            let marker = ExclusionMarkerAccounts {
+                marker: account.clone(),
+                bump,
+                payer: ctx.accounts.payer.to_account_info(),
+                rent_recipient: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            };
+            create_exclusion_marker(&vault_key, &marker, wallet, reason, now)?;
+            created += 1;
+        }
+        
+        let vault = &mut ctx.accounts.vault;
+        vault.reward_exclusions.drain(..count);
+        vault.exclusion_count = vault.exclusion_count
+            .checked_add(created as u64)
+            .ok_or(VaultError::MathOverflow)?;
+        
+        msg!("Migrated {} exclusions ({} remaining)", count, vault.reward_exclusions.len());
+        
+        Ok(())
+    }
+
+    /// Whether a wallet is excluded from rewards (read-only, for CPI or simulation)
+    pub fn is_excluded(ctx: Context<IsExcluded>, wallet: Pubkey) -> Result<bool> {
+        Ok(marker_exists(&ctx.accounts.exclusion_marker)
+            || ctx.accounts.vault.reward_exclusions.contains(&wallet))
+    }
+
+    /// Propose a new authority or keeper authority (admin only)
+    /// Takes effect once the proposed key calls `accept_authority`
+    /// With a signer set enabled, queue `ProposeAuthority` or `ProposeKeeperAuthority` instead
+    pub fn transfer_authority(
+        ctx: Context<ManageAuthority>,
+        role: AuthorityRole,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        
+        require_keys_neq!(new_authority, Pubkey::default(), VaultError::InvalidAuthority);
+        vault.require_distinct_roles(role, new_authority)?;
+        
+        let current = match role {
+            AuthorityRole::Authority => {
+                vault.pending_authority = new_authority;
+                vault.authority
+            }
+            AuthorityRole::Keeper => {
+                vault.pending_keeper_authority = new_authority;
+                vault.keeper_authority
+            }
+        };
+        
+        emit!(AuthorityTransferProposed {
+            vault: vault.key(),
+            role,
+            current,
+            pending: new_authority,
+        });
+        
+        msg!("{:?} transfer proposed to: {}", role, new_authority);
+        Ok(())
+    }
+
+    /// Accept a pending authority or keeper authority transfer (proposed key only)
+    pub fn accept_authority(
+        ctx: Context<AcceptAuthority>,
+        role: AuthorityRole,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let new_authority = ctx.accounts.new_authority.key();
+        
+        require_keys_eq!(
+            vault.pending_for(role),
+            new_authority,
+            VaultError::Unauthorized
+        );
+        vault.require_distinct_roles(role, new_authority)?;
+        
+        let previous = match role {
+            AuthorityRole::Authority => {
+                vault.pending_authority = Pubkey::default();
+                std::mem::replace(&mut vault.authority, new_authority)
+            }
+            AuthorityRole::Keeper => {
+                vault.pending_keeper_authority = Pubkey::default();
+                std::mem::replace(&mut vault.keeper_authority, new_authority)
+            }
+        };
+        
+        emit!(AuthorityTransferAccepted {
+            vault: vault.key(),
+            role,
+            previous,
+            new_authority,
+        });
+        
+        msg!("{:?} transferred to: {}", role, new_authority);
+        Ok(())
+    }
+
+    /// Cancel a pending authority or keeper authority transfer (admin only)
+    pub fn cancel_authority_transfer(
+        ctx: Context<ManageAuthority>,
+        role: AuthorityRole,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        
+        let cancelled = vault.pending_for(role);
+        require_keys_neq!(cancelled, Pubkey::default(), VaultError::NoPendingAuthority);
+        
+        match role {
+            AuthorityRole::Authority => vault.pending_authority = Pubkey::default(),
+            AuthorityRole::Keeper => vault.pending_keeper_authority = Pubkey::default(),
+        }
+        
+        emit!(AuthorityTransferCancelled {
+            vault: vault.key(),
+            role,
+            cancelled,
+        });
+        
+        msg!("{:?} transfer to {} cancelled", role, cancelled);
+        Ok(())
+    }
+
+    /// Queue an admin configuration change (admin only)
+    /// Changes to the owner wallet, hold minimum or harvest threshold wait out `config_delay`,
+    /// emergency withdrawals the shorter `emergency_delay`; anything else can execute once approved.
+    /// With a signer set enabled any admin signer may propose, counting as the first approval.
+    /// The threshold is recorded, so a change queued under a signer set never executes without it.
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        let proposer = ctx.accounts.admin.key();
+        
+        validate_config_change(vault, &change)?;
+        
+        let queued_at = Clock::get()?.unix_timestamp;
+        let execute_after = queued_at
+            .checked_add(change.delay(vault))
+            .ok_or(VaultError::MathOverflow)?;
+        
+        let pending = &mut ctx.accounts.pending_change;
+        pending.vault = vault.key();
+        pending.id = vault.config_change_count;
+        pending.proposer = proposer;
+        pending.change = change.clone();
+        pending.queued_at = queued_at;
+        pending.execute_after = execute_after;
+        pending.approvals = if vault.multisig_enabled() {
+            vec![proposer]
+        } else {
+            Vec::new()
+        };
+        pending.required_approvals = vault.admin_threshold;
+        
+        vault.config_change_count = vault.config_change_count
+            .checked_add(1)
+            .ok_or(VaultError::MathOverflow)?;
+        
+        emit!(ConfigChangeQueued {
+            vault: pending.vault,
+            id: pending.id,
+            change,
+            execute_after,
+        });
+        
+        msg!("Config change {} queued, executable after {}", pending.id, execute_after);
+        
+        Ok(())
+    }
+
+    /// Approve a queued configuration change (admin signer only)
+    pub fn approve_config_change(ctx: Context<ApproveConfigChange>) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        let admin_signers = &ctx.accounts.vault.admin_signers;
+        let pending = &mut ctx.accounts.pending_change;
+        
+        require!(
+            !pending.approvals.contains(&signer),
+            VaultError::AlreadyApproved
+        );
+        // Drop approvals from keys removed from the signer set since queueing
+        pending.approvals.retain(|a| admin_signers.contains(a));
+        pending.approvals.push(signer);
+        
+        let approvals = ctx.accounts.vault.approval_count(&pending.approvals);
+        let threshold = pending.approvals_needed(&ctx.accounts.vault);
+        
+        emit!(ConfigChangeApproved {
+            vault: pending.vault,
+            id: pending.id,
+            signer,
+            approvals,
+            threshold,
+        });
+        
+        msg!("Config change {} approved by {} ({}/{})", pending.id, signer, approvals, threshold);
+        
+        Ok(())
+    }
+
+    /// Execute a queued configuration change once its delay has passed (permissionless)
+    /// Emergency withdrawals need the token accounts; withheld sources go in remaining accounts
+    pub fn execute_config_change<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteConfigChange<'info>>,
+    ) -> Result<()> {
+        let pending = &ctx.accounts.pending_change;
+        let id = pending.id;
+        let change = pending.change.clone();
+        
+        require!(
+            Clock::get()?.unix_timestamp >= pending.execute_after,
+            VaultError::TimelockNotElapsed
+        );
+        // A change queued under a signer set keeps needing it, even once the set is disabled
+        let needed = pending.approvals_needed(&ctx.accounts.vault);
+        if needed > 0 {
+            require!(
+                ctx.accounts.vault.approval_count(&pending.approvals) >= needed,
+                VaultError::InsufficientApprovals
+            );
+        }
+        
+        match &change {
+            ConfigChange::UpdateConfig { update } => {
+                apply_config_update(&mut ctx.accounts.vault, update)?;
+                msg!("Vault configuration updated");
+                
+                let vault = &ctx.accounts.vault;
+                emit!(ConfigUpdated {
+                    vault: vault.key(),
+                    owner_wallet: vault.owner_wallet,
+                    min_hold_amount: vault.min_hold_amount,
+                    harvest_threshold: vault.harvest_threshold,
+                    owner_tax_share: vault.owner_tax_share,
+                    holders_tax_share: vault.holders_tax_share,
+                    reward_dial: vault.reward_dial,
+                    config_delay: vault.config_delay,
+                    emergency_delay: vault.emergency_delay,
+                    withdraw_window: vault.withdraw_window,
+                    max_withdraw_per_window: vault.max_withdraw_per_window,
+                    max_withdraw_bps: vault.max_withdraw_bps,
+                    require_verified_pools: vault.require_verified_pools,
+                    fee_bps_cap: vault.fee_bps_cap,
+                    max_fee_cap: vault.max_fee_cap,
+                    require_launch_pool: vault.require_launch_pool,
+                });
+            }
+            ConfigChange::EmergencyWithdraw { amount, destination } => {
+                emergency_withdraw_vault(ctx.accounts, ctx.bumps.vault, *amount, destination)?;
+                
+                // Only the tracked part of the balance leaves the harvested bucket
+                let vault = &mut ctx.accounts.vault;
+                let tracked = (*amount).min(vault.ledger.harvested);
+                ledger_debit(&mut vault.ledger.harvested, tracked)?;
+                
+                msg!("Emergency withdrawal: {} tokens", amount);
+                
+                emit!(EmergencyWithdrawn {
+                    vault: vault.key(),
+                    source: required(&ctx.accounts.vault_token_account)?.key(),
+                    destination: *destination,
+                    amount: *amount,
+                    ledger: vault.ledger,
+                });
+            }
+            ConfigChange::EmergencyWithdrawTreasury { amount, destination } => {
+                let treasury = required(&ctx.accounts.holder_treasury)?;
+                emergency_transfer(
+                    ctx.accounts,
+                    ctx.bumps.vault,
+                    &treasury.to_account_info(),
+                    *amount,
+                    destination,
+                )?;
+                
+                // Holder share leaves with the treasury balance
+                let treasury_key = treasury.key();
+                let vault = &mut ctx.accounts.vault;
+                let tracked = (*amount).min(vault.ledger.owed_to_holders);
+                ledger_debit(&mut vault.ledger.owed_to_holders, tracked)?;
+                
+                msg!("Emergency withdrawal from holder treasury: {} tokens", amount);
+                
+                emit!(EmergencyWithdrawn {
+                    vault: vault.key(),
+                    source: treasury_key,
+                    destination: *destination,
+                    amount: *amount,
+                    ledger: vault.ledger,
+                });
+            }
+            ConfigChange::EmergencyWithdrawWithheld { destination } => {
+                let batch = emergency_withdraw_withheld(
+                    ctx.accounts,
+                    ctx.bumps.vault,
+                    ctx.remaining_accounts,
+                    destination,
+                )?;
+                
+                msg!("Emergency withdrawal of {} withheld fees from {} accounts", batch.total, batch.harvested.len());
+                
+                emit!(WithheldEmergencyWithdrawn {
+                    vault: ctx.accounts.vault.key(),
+                    destination: *destination,
+                    withdrawn: batch.harvested,
+                    skipped: batch.skipped,
+                    total: batch.total,
+                });
+            }
+            ConfigChange::ManageExclusion { action, wallet, reason } => {
+                let accounts = &mut *ctx.accounts;
+                let account = required(&accounts.exclusion_marker)?;
+                let (expected, bump) = exclusion_marker_address(&accounts.vault.key(), wallet);
+                require_keys_eq!(account.key(), expected, VaultError::InvalidExclusionMarker);
+                
+                let marker = ExclusionMarkerAccounts {
+                    marker: account.to_account_info(),
+                    bump,
+                    payer: accounts.executor.to_account_info(),
+                    rent_recipient: required(&accounts.authority)?.to_account_info(),
+                    system_program: required(&accounts.system_program)?.to_account_info(),
+                };
+                apply_exclusion(&mut accounts.vault, &marker, *action, *wallet, *reason)?;
+            }
+            ConfigChange::SetTransferFee { basis_points, maximum_fee } => {
+                let accounts = &mut *ctx.accounts;
+                let token_mint = required(&accounts.token_mint)?;
+                let token_program = required(&accounts.token_program)?;
+                let history = accounts.fee_history
+                    .as_mut()
+                    .ok_or(VaultError::MissingAccount)?;
+                apply_transfer_fee(
+                    &mut accounts.vault,
+                    ctx.bumps.vault,
+                    token_mint,
+                    token_program,
+                    history,
+                    *basis_points,
+                    *maximum_fee,
+                )?;
+            }
+            ConfigChange::SetFeeSchedule { steps } => {
+                apply_fee_schedule(&mut ctx.accounts.vault, steps.clone())?;
+            }
+            ConfigChange::ManageDexAllowlist { action, dex } => {
+                let vault_key = ctx.accounts.vault.key();
+                let allowlist = ctx.accounts.dex_allowlist
+                    .as_mut()
+                    .ok_or(VaultError::MissingAccount)?;
+                apply_dex_allowlist(allowlist, vault_key, *action, *dex)?;
+            }
+            ConfigChange::SetAdminSigners { signers, threshold } => {
+                let vault = &mut ctx.accounts.vault;
+                validate_admin_signers(vault, signers, *threshold)?;
+                vault.admin_signers = signers.clone();
+                vault.admin_threshold = *threshold;
+                msg!("Admin signer set updated: {}-of-{}", threshold, signers.len());
+                
+                emit!(AdminSignersUpdated {
+                    vault: vault.key(),
+                    signers: signers.clone(),
+                    threshold: *threshold,
+                });
+            }
+            ConfigChange::ProposeKeeperAuthority { keeper } => {
+                let vault = &mut ctx.accounts.vault;
+                vault.require_distinct_roles(AuthorityRole::Keeper, *keeper)?;
+                vault.pending_keeper_authority = *keeper;
+                msg!("Keeper transfer proposed to: {}", keeper);
+                
+                emit!(AuthorityTransferProposed {
+                    vault: vault.key(),
+                    role: AuthorityRole::Keeper,
+                    current: vault.keeper_authority,
+                    pending: *keeper,
+                });
+            }
+            ConfigChange::ProposeAuthority { authority } => {
+                let vault = &mut ctx.accounts.vault;
+                vault.require_distinct_roles(AuthorityRole::Authority, *authority)?;
+                vault.pending_authority = *authority;
+                msg!("Authority transfer proposed to: {}", authority);
+                
+                emit!(AuthorityTransferProposed {
+                    vault: vault.key(),
+                    role: AuthorityRole::Authority,
+                    current: vault.authority,
+                    pending: *authority,
+                });
+            }
+        }
         
-        msg!("Emergency withdrawal of withheld fees from {} accounts", accounts.len());
+        emit!(ConfigChangeExecuted {
+            vault: ctx.accounts.vault.key(),
+            id,
+            change,
+            executor: ctx.accounts.executor.key(),
+        });
+        
+        Ok(())
+    }
+
+    /// Cancel a queued configuration change (admin only)
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        let pending = &ctx.accounts.pending_change;
+        
+        emit!(ConfigChangeCancelled {
+            vault: pending.vault,
+            id: pending.id,
+        });
+        
+        msg!("Config change {} cancelled", pending.id);
+        
+        Ok(())
+    }
+
+    /// Upgrade the vault account to the current layout in place (authority only)
+    /// Reallocs to the current size and fills new fields with their defaults, no-op once current
+    /// With a signer set enabled, admin signers meeting the threshold sign as remaining accounts.
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        let info = ctx.accounts.vault.to_account_info();
+        let space = 8 + VaultState::INIT_SPACE;
+        
+        let (vault, from_version) = VaultState::decode_any(&info.try_borrow_data()?)?;
+        require_keys_eq!(vault.authority, ctx.accounts.authority.key(), VaultError::Unauthorized);
+        require_admin_threshold(&vault, ctx.accounts.authority.key, ctx.remaining_accounts)?;
+        if from_version == VAULT_STATE_VERSION {
+            msg!("Vault already at version {}", VAULT_STATE_VERSION);
+            return Ok(());
+        }
+        
+        realloc_account(&info, &ctx.accounts.authority, &ctx.accounts.system_program, space)?;
+        vault.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        
+        msg!("Vault migrated from version {} to {}", from_version, VAULT_STATE_VERSION);
+        
+        emit!(AccountMigrated {
+            account: info.key(),
+            from_version,
+            to_version: VAULT_STATE_VERSION,
+        });
+        
+        Ok(())
+    }
+
+    /// Upgrade pool registry page 0 to the current layout in place (authority only)
+    /// Legacy entries keep their key as the pool address with empty metadata
+    /// With a signer set enabled, admin signers meeting the threshold sign as remaining accounts.
+    pub fn migrate_pool_registry(ctx: Context<MigratePoolRegistry>) -> Result<()> {
+        let info = ctx.accounts.pool_registry.to_account_info();
+        let space = 8 + PoolRegistry::INIT_SPACE;
+        
+        require_admin_threshold(&ctx.accounts.vault, ctx.accounts.authority.key, ctx.remaining_accounts)?;
+        require!(
+            &info.try_borrow_data()?[..8] == PoolRegistry::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        
+        if info.data_len() >= space {
+            let data = info.try_borrow_data()?;
+            let registry = PoolRegistry::try_deserialize(&mut &data[..])?;
+            require!(registry.version == POOL_REGISTRY_VERSION, VaultError::UnsupportedVersion);
+            msg!("Pool registry already at version {}", registry.version);
+            return Ok(());
+        }
+        
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            PoolRegistryV0::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(legacy.vault, ctx.accounts.vault.key(), VaultError::InvalidRegistryPage);
+        
+        let registry = PoolRegistry::from_v0(legacy, Clock::get()?.unix_timestamp);
+        
+        realloc_account(&info, &ctx.accounts.authority, &ctx.accounts.system_program, space)?;
+        registry.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        
+        // Page 0 already existed before registry pages were tracked
+        let vault = &mut ctx.accounts.vault;
+        vault.pool_registry_pages = vault.pool_registry_pages.max(1);
+        
+        msg!("Pool registry migrated with {} pools", registry.pools.len());
+        
+        emit!(AccountMigrated {
+            account: info.key(),
+            from_version: 0,
+            to_version: POOL_REGISTRY_VERSION,
+        });
+        
+        Ok(())
+    }
+
+    /// Upgrade the pre-paging keeper work log in place (authority only)
+    /// Its entries are kept read-only, new work is logged to `KeeperWorkLogPage`s
+    /// With a signer set enabled, admin signers meeting the threshold sign as remaining accounts.
+    pub fn migrate_keeper_work_log(ctx: Context<MigrateKeeperWorkLog>) -> Result<()> {
+        let info = ctx.accounts.keeper_work_log.to_account_info();
+        let space = 8 + KeeperWorkLog::INIT_SPACE;
+        
+        require_admin_threshold(&ctx.accounts.vault, ctx.accounts.authority.key, ctx.remaining_accounts)?;
+        require!(
+            &info.try_borrow_data()?[..8] == KeeperWorkLog::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        
+        if info.data_len() >= space {
+            let data = info.try_borrow_data()?;
+            let log = KeeperWorkLog::try_deserialize(&mut &data[..])?;
+            require!(log.version == KEEPER_WORK_LOG_VERSION, VaultError::UnsupportedVersion);
+            msg!("Keeper work log already at version {}", log.version);
+            return Ok(());
+        }
+        
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            KeeperWorkLogV0::deserialize(&mut &data[8..])?
+        };
+        let log = KeeperWorkLog {
+            vault: legacy.vault,
+            entries: legacy.entries,
+            version: KEEPER_WORK_LOG_VERSION,
+        };
+        
+        realloc_account(&info, &ctx.accounts.authority, &ctx.accounts.system_program, space)?;
+        log.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        
+        msg!("Keeper work log migrated with {} entries", log.entries.len());
+        
+        emit!(AccountMigrated {
+            account: info.key(),
+            from_version: 0,
+            to_version: KEEPER_WORK_LOG_VERSION,
+        });
         
         Ok(())
     }
 }
 
-// Account structures
+// Under a signer set the authority alone cannot act, enough admin signers must sign with it
+fn require_admin_threshold(vault: &VaultState, authority: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+    if !vault.multisig_enabled() {
+        return Ok(());
+    }
+    let mut signers = vec![*authority];
+    for account in accounts.iter().filter(|account| account.is_signer) {
+        if !signers.contains(account.key) {
+            signers.push(*account.key);
+        }
+    }
+    require!(
+        vault.approval_count(&signers) >= vault.admin_threshold,
+        VaultError::InsufficientApprovals
+    );
+    Ok(())
+}
+
+// Grow a program-owned account, topping up rent from the payer
+fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let shortfall = required.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+// Check a config change against bounds before it is queued or applied
+fn validate_config_change(vault: &VaultState, change: &ConfigChange) -> Result<()> {
+    match change {
+        ConfigChange::UpdateConfig { update } => validate_config_update(vault, update)?,
+        ConfigChange::EmergencyWithdraw { amount, .. }
+        | ConfigChange::EmergencyWithdrawTreasury { amount, .. } => {
+            require!(*amount > 0, VaultError::InvalidAmount);
+        }
+        ConfigChange::EmergencyWithdrawWithheld { .. } => {}
+        ConfigChange::ManageExclusion { .. } => {}
+        ConfigChange::ManageDexAllowlist { .. } => {}
+        ConfigChange::SetTransferFee { basis_points, maximum_fee } => {
+            validate_transfer_fee(vault, *basis_points, *maximum_fee)?;
+        }
+        ConfigChange::SetFeeSchedule { steps } => {
+            validate_fee_schedule(vault, steps, min_fee_step_spacing()?)?;
+        }
+        ConfigChange::SetAdminSigners { signers, threshold } => {
+            validate_admin_signers(vault, signers, *threshold)?;
+        }
+        ConfigChange::ProposeKeeperAuthority { keeper } => {
+            require_keys_neq!(*keeper, Pubkey::default(), VaultError::InvalidAuthority);
+            vault.require_distinct_roles(AuthorityRole::Keeper, *keeper)?;
+        }
+        ConfigChange::ProposeAuthority { authority } => {
+            require_keys_neq!(*authority, Pubkey::default(), VaultError::InvalidAuthority);
+            require_keys_neq!(*authority, vault.authority, VaultError::InvalidAuthority);
+            vault.require_distinct_roles(AuthorityRole::Authority, *authority)?;
+        }
+    }
+    Ok(())
+}
+
+// An empty set with threshold 0 returns control to the single authority
+fn validate_admin_signers(vault: &VaultState, signers: &[Pubkey], threshold: u8) -> Result<()> {
+    if signers.is_empty() {
+        require!(threshold == 0, VaultError::InvalidSignerSet);
+        return Ok(());
+    }
+    require!(
+        signers.len() <= MAX_ADMIN_SIGNERS
+            && threshold > 0
+            && threshold as usize <= signers.len(),
+        VaultError::InvalidSignerSet
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(
+            *signer != Pubkey::default()
+                && *signer != vault.keeper_authority
+                && !signers[..i].contains(signer),
+            VaultError::InvalidSignerSet
+        );
+    }
+    Ok(())
+}
+
+// Accounts needed to create or close an exclusion marker
+struct ExclusionMarkerAccounts<'info> {
+    marker: AccountInfo<'info>,
+    bump: u8,
+    payer: AccountInfo<'info>,
+    rent_recipient: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+}
+
+fn exclusion_marker_address(vault: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EXCLUSION_SEED, vault.as_ref(), wallet.as_ref()], &crate::ID)
+}
+
+// Roles and program accounts excluded from the start, in initialize's marker order
+fn system_exclusions(vault: &VaultState, vault_key: Pubkey) -> Vec<Pubkey> {
+    let mut wallets = Vec::new();
+    for wallet in [vault.authority, vault.keeper_authority, vault.owner_wallet, vault_key, crate::ID] {
+        if !wallets.contains(&wallet) {
+            wallets.push(wallet);
+        }
+    }
+    wallets
+}
+
+fn marker_exists(marker: &AccountInfo) -> bool {
+    marker.owner == &crate::ID && !marker.data_is_empty()
+}
+
+fn apply_exclusion(
+    vault: &mut Account<VaultState>,
+    accounts: &ExclusionMarkerAccounts,
+    action: ExclusionAction,
+    wallet: Pubkey,
+    reason: ExclusionReason,
+) -> Result<()> {
+    let vault_key = vault.key();
+    let reason = match action {
+        ExclusionAction::Add => {
+            require!(
+                !marker_exists(&accounts.marker) && !vault.reward_exclusions.contains(&wallet),
+                VaultError::AlreadyExcluded
+            );
+            create_exclusion_marker(&vault_key, accounts, wallet, reason, Clock::get()?.unix_timestamp)?;
+            vault.exclusion_count = vault.exclusion_count
+                .checked_add(1)
+                .ok_or(VaultError::MathOverflow)?;
+            msg!("Added {} to reward exclusions ({:?})", wallet, reason);
+            reason
+        }
+        ExclusionAction::Remove => {
+            // Entries not yet migrated are dropped from the legacy list instead
+            let legacy = vault.reward_exclusions.len();
+            vault.reward_exclusions.retain(|&x| x != wallet);
+            let reason = if vault.reward_exclusions.len() < legacy {
+                ExclusionReason::Manual
+            } else {
+                require!(marker_exists(&accounts.marker), VaultError::NotExcluded);
+                let stored = close_exclusion_marker(accounts)?;
+                vault.exclusion_count = vault.exclusion_count
+                    .checked_sub(1)
+                    .ok_or(VaultError::MathOverflow)?;
+                stored
+            };
+            msg!("Removed {} from reward exclusions", wallet);
+            reason
+        }
+    };
+    
+    emit!(ExclusionUpdated {
+        vault: vault_key,
+        wallet,
+        action,
+        reason,
+        exclusion_count: vault.exclusion_count,
+        legacy_exclusions: vault.reward_exclusions.len() as u32,
+    });
+    
+    Ok(())
+}
+
+fn create_exclusion_marker(
+    vault_key: &Pubkey,
+    accounts: &ExclusionMarkerAccounts,
+    wallet: Pubkey,
+    reason: ExclusionReason,
+    now: i64,
+) -> Result<()> {
+    let space = 8 + ExclusionMarker::INIT_SPACE;
+    let seeds = &[
+        EXCLUSION_SEED,
+        vault_key.as_ref(),
+        wallet.as_ref(),
+        &[accounts.bump],
+    ];
+    
+    create_pda_account(
+        &accounts.marker,
+        &accounts.payer,
+        &accounts.system_program,
+        space,
+        &[&seeds[..]],
+    )?;
+    
+    let marker = ExclusionMarker {
+        vault: *vault_key,
+        wallet,
+        reason,
+        excluded_at: now,
+        bump: accounts.bump,
+    };
+    let mut data = accounts.marker.try_borrow_mut_data()?;
+    marker.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+// Create a program-owned PDA, as Anchor's `init` does
+// Lamports sent to the address beforehand would make `create_account` fail, so a funded
+// address is topped up to rent exemption, then allocated and assigned instead
+fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    
+    if lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+    
+    let shortfall = rent.saturating_sub(lamports);
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )
+}
+
+// Returns the reason the closed marker was created with
+fn close_exclusion_marker(accounts: &ExclusionMarkerAccounts) -> Result<ExclusionReason> {
+    let reason = {
+        let data = accounts.marker.try_borrow_data()?;
+        ExclusionMarker::try_deserialize(&mut &data[..])?.reason
+    };
+    
+    let lamports = accounts.marker.lamports();
+    **accounts.rent_recipient.try_borrow_mut_lamports()? = accounts.rent_recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(VaultError::MathOverflow)?;
+    **accounts.marker.try_borrow_mut_lamports()? = 0;
+    accounts.marker.assign(&system_program::ID);
+    accounts.marker.resize(0)?;
+    Ok(reason)
+}
+
+fn apply_dex_allowlist(
+    allowlist: &mut DexAllowlist,
+    vault_key: Pubkey,
+    action: AllowlistAction,
+    dex: DexProgram,
+) -> Result<()> {
+    match action {
+        AllowlistAction::Add => {
+            require!(
+                allowlist.find(&dex.program_id).is_none(),
+                VaultError::DexAlreadyAllowlisted
+            );
+            require!(
+                allowlist.programs.len() < MAX_DEX_PROGRAMS,
+                VaultError::DexAllowlistFull
+            );
+            allowlist.programs.push(dex);
+            msg!("Allowlisted {:?} program {}", dex.kind, dex.program_id);
+        }
+        AllowlistAction::Remove => {
+            allowlist.programs.retain(|entry| entry.program_id != dex.program_id);
+            msg!("Removed DEX program {} from allowlist", dex.program_id);
+        }
+    }
+    
+    emit!(DexAllowlistUpdated {
+        vault: vault_key,
+        action,
+        dex,
+        total_programs: allowlist.programs.len() as u32,
+    });
+    
+    Ok(())
+}
+
+fn validate_transfer_fee(vault: &VaultState, basis_points: u16, maximum_fee: u64) -> Result<()> {
+    // Would replace the schedule step waiting on the mint
+    require!(vault.pending_fee_step.is_none(), VaultError::FeeStepPending);
+    require!(
+        basis_points <= vault.fee_bps_cap && maximum_fee <= vault.max_fee_cap,
+        VaultError::TransferFeeAboveCap
+    );
+    Ok(())
+}
+
+// CPI into Token-2022 as the mint's transfer fee config authority and record the change
+fn apply_transfer_fee<'info>(
+    vault: &mut Account<'info, VaultState>,
+    vault_bump: u8,
+    token_mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    history: &mut FeeRateHistory,
+    basis_points: u16,
+    maximum_fee: u64,
+) -> Result<()> {
+    validate_transfer_fee(vault, basis_points, maximum_fee)?;
+    require_keys_eq!(token_mint.key(), vault.token_mint, VaultError::InvalidTokenMint);
+    
+    let token_mint_key = vault.token_mint;
+    let seeds = &[
+        VAULT_SEED,
+        token_mint_key.as_ref(),
+        &[vault_bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    let ix = set_transfer_fee(
+        &token_program.key(),
+        &token_mint.key(),
+        &vault.key(),
+        &[],
+        basis_points,
+        maximum_fee,
+    )?;
+    invoke_signed(
+        &ix,
+        &[token_mint.clone(), vault.to_account_info()],
+        signer_seeds,
+    )?;
+    
+    let clock = Clock::get()?;
+    vault.transfer_fee = read_transfer_fee(token_mint, clock.epoch)?;
+    history.record(FeeRateChange {
+        changed_at: clock.unix_timestamp,
+        effective_epoch: vault.transfer_fee.pending_epoch,
+        basis_points,
+        maximum_fee,
+    });
+    
+    msg!("Transfer fee set to {} bps, active from epoch {}", basis_points, vault.transfer_fee.pending_epoch);
+    
+    emit!(TransferFeeSet {
+        vault: vault.key(),
+        basis_points,
+        maximum_fee,
+        effective_epoch: vault.transfer_fee.pending_epoch,
+        active_basis_points: vault.transfer_fee.active_basis_points,
+        active_maximum_fee: vault.transfer_fee.active_maximum_fee,
+    });
+    
+    Ok(())
+}
+
+// Two epochs at the nominal slot time, the least Token-2022 needs to activate a new rate
+fn min_fee_step_spacing() -> Result<i64> {
+    let slots = EpochSchedule::get()?.slots_per_epoch;
+    Ok((2 * slots).saturating_mul(DEFAULT_MS_PER_SLOT) as i64 / 1000)
+}
+
+// Steps start at launch, each at least `min_spacing` seconds after the last and within the fee caps
+// Spacing is two epochs (about four days on mainnet), so minute-long launch steps cannot be expressed;
+// the first step is the rate the mint is created with and must be on the mint by launch
+fn validate_fee_schedule(vault: &VaultState, steps: &[FeeStep], min_spacing: i64) -> Result<()> {
+    require!(vault.launch_timestamp == 0, VaultError::FeeScheduleLocked);
+    require!(
+        !steps.is_empty()
+            && steps.len() <= MAX_FEE_STEPS
+            && steps[0].starts_after == 0
+            && steps.windows(2).all(|pair| {
+                pair[1].starts_after
+                    .checked_sub(pair[0].starts_after)
+                    .is_some_and(|gap| gap > 0 && gap >= min_spacing)
+            }),
+        VaultError::InvalidFeeSchedule
+    );
+    for step in steps {
+        validate_transfer_fee(vault, step.basis_points, step.maximum_fee)?;
+    }
+    Ok(())
+}
+
+fn apply_fee_schedule(vault: &mut Account<VaultState>, steps: Vec<FeeStep>) -> Result<()> {
+    validate_fee_schedule(vault, &steps, min_fee_step_spacing()?)?;
+    
+    vault.fee_schedule = steps;
+    vault.active_fee_step = None;
+    vault.pending_fee_step = None;
+    
+    msg!("Fee schedule set with {} steps", vault.fee_schedule.len());
+    
+    emit!(FeeScheduleSet {
+        vault: vault.key(),
+        steps: vault.fee_schedule.clone(),
+    });
+    
+    Ok(())
+}
+
+// Active rate for `epoch` and the newer rate if it has not taken effect yet
+fn read_transfer_fee(token_mint: &AccountInfo, epoch: u64) -> Result<TransferFeeRates> {
+    let data = token_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    let config = mint.get_extension::<TransferFeeConfig>()?;
+    
+    let active = config.get_epoch_fee(epoch);
+    let newer = &config.newer_transfer_fee;
+    let has_pending = u64::from(newer.epoch) > epoch;
+    let pending = if has_pending { newer } else { active };
+    
+    Ok(TransferFeeRates {
+        active_basis_points: u16::from(active.transfer_fee_basis_points),
+        active_maximum_fee: u64::from(active.maximum_fee),
+        pending_basis_points: u16::from(pending.transfer_fee_basis_points),
+        pending_maximum_fee: u64::from(pending.maximum_fee),
+        pending_epoch: if has_pending { u64::from(newer.epoch) } else { 0 },
+    })
+}
+
+fn validate_config_update(vault: &VaultState, update: &ConfigUpdate) -> Result<()> {
+    let owner_share = update.owner_tax_share.unwrap_or(vault.owner_tax_share);
+    let holders_share = update.holders_tax_share.unwrap_or(vault.holders_tax_share);
+    require!(
+        owner_share.checked_add(holders_share) == Some(100),
+        VaultError::InvalidDistributionSplit
+    );
+    for delay in [update.config_delay, update.emergency_delay].into_iter().flatten() {
+        require!(
+            (0..=MAX_CONFIG_DELAY).contains(&delay),
+            VaultError::InvalidTimelock
+        );
+    }
+    if let Some(window) = update.withdraw_window {
+        require!(window > 0, VaultError::InvalidRateLimit);
+    }
+    if let Some(bps) = update.max_withdraw_bps {
+        require!(
+            bps > 0 && bps as u64 <= BPS_DENOMINATOR,
+            VaultError::InvalidRateLimit
+        );
+    }
+    if let Some(cap) = update.fee_bps_cap {
+        require!(cap <= MAX_FEE_BASIS_POINTS, VaultError::InvalidFeeCap);
+    }
+    Ok(())
+}
+
+// Re-validated at execution since vault state may have moved since queueing
+fn apply_config_update(vault: &mut VaultState, update: &ConfigUpdate) -> Result<()> {
+    validate_config_update(vault, update)?;
+    
+    if let Some(owner) = update.owner_wallet {
+        vault.owner_wallet = owner;
+    }
+    if let Some(min_hold) = update.min_hold_amount {
+        vault.min_hold_amount = min_hold;
+    }
+    if let Some(threshold) = update.harvest_threshold {
+        vault.harvest_threshold = threshold;
+    }
+    if let Some(owner_share) = update.owner_tax_share {
+        vault.owner_tax_share = owner_share;
+    }
+    if let Some(holders_share) = update.holders_tax_share {
+        vault.holders_tax_share = holders_share;
+    }
+    if let Some(reward_dial) = update.reward_dial {
+        vault.reward_dial = reward_dial;
+    }
+    if let Some(delay) = update.config_delay {
+        vault.config_delay = delay;
+    }
+    if let Some(delay) = update.emergency_delay {
+        vault.emergency_delay = delay;
+    }
+    if let Some(window) = update.withdraw_window {
+        vault.withdraw_window = window;
+    }
+    if let Some(max_per_window) = update.max_withdraw_per_window {
+        vault.max_withdraw_per_window = max_per_window;
+    }
+    if let Some(bps) = update.max_withdraw_bps {
+        vault.max_withdraw_bps = bps;
+    }
+    if let Some(require_verified) = update.require_verified_pools {
+        vault.require_verified_pools = require_verified;
+    }
+    if let Some(cap) = update.fee_bps_cap {
+        vault.fee_bps_cap = cap;
+    }
+    if let Some(cap) = update.max_fee_cap {
+        vault.max_fee_cap = cap;
+    }
+    if let Some(require_pool) = update.require_launch_pool {
+        vault.require_launch_pool = require_pool;
+    }
+    Ok(())
+}
+
+// The keeper may take part of the owner share to fund its own transactions, only while low on SOL
+fn check_keeper_allowance(owner_amount: u64, allowance: u64, keeper_lamports: u64) -> Result<()> {
+    if allowance == 0 {
+        return Ok(());
+    }
+    require!(allowance <= owner_amount, VaultError::KeeperAllowanceTooHigh);
+    require!(keeper_lamports < KEEPER_TOP_UP_THRESHOLD, VaultError::KeeperTopUpNotNeeded);
+    Ok(())
+}
+
+fn ledger_credit(bucket: &mut u64, amount: u64) -> Result<()> {
+    *bucket = bucket.checked_add(amount).ok_or(VaultError::MathOverflow)?;
+    Ok(())
+}
+
+fn ledger_debit(bucket: &mut u64, amount: u64) -> Result<()> {
+    *bucket = bucket.checked_sub(amount).ok_or(VaultError::MathOverflow)?;
+    Ok(())
+}
+
+// None of `pools` may be registered on a page other than `page`
+// Those pages come in page order; owner, discriminator and stored page pin each to its PDA
+fn check_other_registry_pages(
+    vault: &Account<VaultState>,
+    page: u16,
+    others: &[AccountInfo],
+    pools: &[Pubkey],
+) -> Result<()> {
+    require!(
+        others.len() + 1 == vault.pool_registry_pages as usize,
+        VaultError::RegistryPagesMismatch
+    );
+    
+    let expected_pages = (0..vault.pool_registry_pages).filter(|other| *other != page);
+    for (info, expected) in others.iter().zip(expected_pages) {
+        require_keys_eq!(*info.owner, crate::ID, VaultError::RegistryPagesMismatch);
+        let data = info.try_borrow_data()?;
+        require!(
+            data.get(8) == Some(&POOL_REGISTRY_VERSION),
+            VaultError::AccountNotMigrated
+        );
+        let other = PoolRegistry::try_deserialize(&mut &data[..])?;
+        require!(
+            other.vault == vault.key() && other.page == expected,
+            VaultError::RegistryPagesMismatch
+        );
+        require!(
+            !other.pools.iter().any(|entry| pools.contains(&entry.pool)),
+            VaultError::PoolAlreadyRegistered
+        );
+    }
+    Ok(())
+}
+
+// Page 0 keeps the original registry seeds, later pages append their index
+fn registry_page_seed(page: u16) -> Vec<u8> {
+    if page == 0 {
+        Vec::new()
+    } else {
+        page.to_le_bytes().to_vec()
+    }
+}
+
+fn required<T>(account: &Option<T>) -> Result<&T> {
+    account.as_ref().ok_or_else(|| error!(VaultError::MissingAccount))
+}
+
+// Token accounts shared by both emergency withdrawal paths, checked against the vault
+fn emergency_accounts<'a, 'info>(
+    accounts: &'a ExecuteConfigChange<'info>,
+    destination: &Pubkey,
+) -> Result<(&'a UncheckedAccount<'info>, &'a InterfaceAccount<'info, TokenAccount>, &'a Program<'info, Token2022>)> {
+    let token_mint = required(&accounts.token_mint)?;
+    let destination_token_account = required(&accounts.destination_token_account)?;
+    let token_program = required(&accounts.token_program)?;
+    
+    require_keys_eq!(token_mint.key(), accounts.vault.token_mint, VaultError::InvalidTokenMint);
+    require_keys_eq!(
+        destination_token_account.key(),
+        *destination,
+        VaultError::InvalidDestinationTokenAccount
+    );
+    require_keys_eq!(
+        destination_token_account.mint,
+        accounts.vault.token_mint,
+        VaultError::InvalidDestinationTokenAccount
+    );
+    
+    Ok((token_mint, destination_token_account, token_program))
+}
+
+fn emergency_withdraw_vault(
+    accounts: &ExecuteConfigChange,
+    vault_bump: u8,
+    amount: u64,
+    destination: &Pubkey,
+) -> Result<()> {
+    let vault_token_account = required(&accounts.vault_token_account)?;
+    require_keys_eq!(
+        vault_token_account.key(),
+        vault_token_address(&accounts.vault.key(), &accounts.vault.token_mint),
+        VaultError::InvalidVaultTokenAccount
+    );
+    emergency_transfer(
+        accounts,
+        vault_bump,
+        &vault_token_account.to_account_info(),
+        amount,
+        destination,
+    )
+}
+
+// Move `amount` out of a vault-owned token account to the queued destination
+fn emergency_transfer<'info>(
+    accounts: &ExecuteConfigChange<'info>,
+    vault_bump: u8,
+    from: &AccountInfo<'info>,
+    amount: u64,
+    destination: &Pubkey,
+) -> Result<()> {
+    let vault = &accounts.vault;
+    let (token_mint, destination_token_account, token_program) =
+        emergency_accounts(accounts, destination)?;
+    
+    let seeds = &[
+        VAULT_SEED,
+        vault.token_mint.as_ref(),
+        &[vault_bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    // Get mint decimals
+    let mint_data = token_mint.to_account_info();
+    let mint_data_borrowed = mint_data.try_borrow_data()?;
+    let mint_info = StateWithExtensions::<MintState>::unpack(&mint_data_borrowed)?;
+    let decimals = mint_info.base.decimals;
+    drop(mint_data_borrowed);
+    
+    token_2022::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token_2022::TransferChecked {
+                from: from.clone(),
+                mint: token_mint.to_account_info(),
+                to: destination_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        decimals,
+    )
+}
+
+fn emergency_withdraw_withheld<'info>(
+    accounts: &ExecuteConfigChange<'info>,
+    vault_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+    destination: &Pubkey,
+) -> Result<WithheldBatch<'info>> {
+    let vault = &accounts.vault;
+    let (token_mint, destination_token_account, token_program) =
+        emergency_accounts(accounts, destination)?;
+    
+    let batch = collect_withheld_sources(remaining_accounts, &vault.token_mint)?;
+    require!(!batch.sources.is_empty(), VaultError::NothingToWithdraw);
+    
+    let seeds = &[
+        VAULT_SEED,
+        vault.token_mint.as_ref(),
+        &[vault_bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+    
+    let source_keys: Vec<&Pubkey> = batch.sources.iter().map(|a| a.key).collect();
+    
+    let ix = withdraw_withheld_tokens_from_accounts(
+        &token_program.key(),
+        &token_mint.key(),
+        &destination_token_account.key(),
+        &vault.key(),
+        &source_keys,
+        &[],
+    )?;
+    
+    let mut account_infos = vec![
+        token_mint.to_account_info(),
+        destination_token_account.to_account_info(),
+        vault.to_account_info(),
+    ];
+    account_infos.extend(batch.sources.iter().cloned());
+    
+    invoke_signed(
+        &ix,
+        &account_infos,
+        signer_seeds,
+    )?;
+    
+    Ok(batch)
+}
+
+// Vault token account is the vault PDA's Token-2022 associated token account
+fn vault_token_address(vault: &Pubkey, token_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(vault, token_mint, &token_2022::ID)
+}
+
+// Read fees withheld on the mint and ready for withdrawal
+fn read_mint_withheld(token_mint: &AccountInfo) -> Result<u64> {
+    let data = token_mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    let config = mint.get_extension::<TransferFeeConfig>()?;
+    Ok(u64::from(config.withheld_amount))
+}
+
+// Read fees withheld on a token account of `token_mint`, not yet harvested to the mint
+fn read_account_withheld(token_account: &AccountInfo, token_mint: &Pubkey) -> Result<u64> {
+    require_keys_eq!(
+        *token_account.owner,
+        token_2022::ID,
+        VaultError::InvalidSourceAccount
+    );
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    require_keys_eq!(account.base.mint, *token_mint, VaultError::InvalidSourceAccount);
+    Ok(account
+        .get_extension::<TransferFeeAmount>()
+        .map(|fee| u64::from(fee.withheld_amount))
+        .unwrap_or(0))
+}
+
+// Validated batch of withheld fee sources taken from remaining accounts
+struct WithheldBatch<'info> {
+    sources: Vec<AccountInfo<'info>>,
+    harvested: Vec<AccountHarvest>,
+    skipped: Vec<Pubkey>,
+    total: u64,
+}
+
+// Validate remaining accounts as Token-2022 accounts of `token_mint`,
+// dropping any that have nothing withheld
+fn collect_withheld_sources<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    token_mint: &Pubkey,
+) -> Result<WithheldBatch<'info>> {
+    require!(
+        !remaining_accounts.is_empty() && remaining_accounts.len() <= MAX_HARVEST_BATCH,
+        VaultError::InvalidBatchSize
+    );
+    
+    let mut batch = WithheldBatch {
+        sources: Vec::with_capacity(remaining_accounts.len()),
+        harvested: Vec::with_capacity(remaining_accounts.len()),
+        skipped: Vec::new(),
+        total: 0,
+    };
+    
+    require_unique_accounts(remaining_accounts)?;
+    
+    for account in remaining_accounts.iter() {
+        require!(account.is_writable, VaultError::InvalidSourceAccount);
+        let withheld = read_account_withheld(account, token_mint)?;
+        if withheld == 0 {
+            batch.skipped.push(account.key());
+            continue;
+        }
+        batch.total = batch.total
+            .checked_add(withheld)
+            .ok_or(VaultError::MathOverflow)?;
+        batch.harvested.push(AccountHarvest {
+            account: account.key(),
+            amount: withheld,
+        });
+        batch.sources.push(account.clone());
+    }
+    
+    Ok(batch)
+}
+
+// Reject an account passed more than once, it would be counted twice
+fn require_unique_accounts(accounts: &[AccountInfo]) -> Result<()> {
+    for (index, account) in accounts.iter().enumerate() {
+        require!(
+            accounts[..index].iter().all(|other| other.key != account.key),
+            VaultError::DuplicateAccount
+        );
+    }
+    Ok(())
+}
+
+// Verify a merkle proof built with sorted sibling pairs
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut node = leaf;
+    for sibling in proof {
+        node = if node <= *sibling {
+            hashv(&[&[MERKLE_NODE_PREFIX], &node, sibling]).to_bytes()
+        } else {
+            hashv(&[&[MERKLE_NODE_PREFIX], sibling, &node]).to_bytes()
+        };
+    }
+    node == root
+}
+
+// Read the current reward basket mints from a Smart Dial state account.
+// DialState starts with its version byte, `authority` and `current_reward_token`, then the
+// fixed-size basket: a length byte followed by DIAL_MAX_BASKET entries that each start with the mint.
+fn read_dial_reward_basket(dial_state: &AccountInfo) -> Result<Vec<Pubkey>> {
+    require_keys_eq!(*dial_state.owner, SMART_DIAL_ID, VaultError::InvalidRewardDial);
+    let data = dial_state.try_borrow_data()?;
+    let discriminator = &hashv(&[b"account:DialState"]).to_bytes()[..8];
+    let basket_end = DIAL_BASKET_OFFSET + 1 + DIAL_MAX_BASKET * DIAL_BASKET_ENTRY_LEN;
+    require!(
+        data.len() >= basket_end && &data[..8] == discriminator,
+        VaultError::InvalidRewardDial
+    );
+    // A dial still awaiting migration has its basket elsewhere
+    require!(data[8] == DIAL_LAYOUT_VERSION, VaultError::AccountNotMigrated);
+    
+    let len = data[DIAL_BASKET_OFFSET] as usize;
+    require!(len > 0 && len <= DIAL_MAX_BASKET, VaultError::InvalidRewardDial);
+    Ok((0..len)
+        .map(|index| {
+            let start = DIAL_BASKET_OFFSET + 1 + index * DIAL_BASKET_ENTRY_LEN;
+            Pubkey::try_from(&data[start..start + 32]).unwrap()
+        })
+        .collect())
+}
+
+// Account structures
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VaultState::INIT_SPACE,
+        seeds = [VAULT_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Token mint
+    pub token_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Vault program ID
+    #[account(address = crate::ID)]
+    pub vault_program: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u16)]
+pub struct InitializePoolRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PoolRegistry::INIT_SPACE,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref(), &registry_page_seed(page)],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    
+    #[account(
+        mut,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLaunchTime<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == signer.key()
+            || (vault.authority == signer.key() && !vault.multisig_enabled()) @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(
+        has_one = vault @ VaultError::InvalidRegistryPage,
+        constraint = pool_registry.version == POOL_REGISTRY_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub pool_registry: Option<Account<'info, PoolRegistry>>,
+    
+    /// CHECK: Registered AMM pool state, owner checked against the registry entry
+    pub pool_state: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u16)]
+pub struct UpdatePoolRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref(), &registry_page_seed(page)],
+        bump,
+        constraint = pool_registry.version == POOL_REGISTRY_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    
+    #[account(
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub keeper_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u16)]
+pub struct AddVerifiedPool<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref(), &registry_page_seed(page)],
+        bump,
+        constraint = pool_registry.version == POOL_REGISTRY_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+    
+    #[account(
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(
+        seeds = [DEX_ALLOWLIST_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub dex_allowlist: Account<'info, DexAllowlist>,
+    
+    /// CHECK: AMM pool state, owner checked against the allowlist and decoded by layout
+    pub pool_state: UncheckedAccount<'info>,
+    
+    pub keeper_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDexAllowlist<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + DexAllowlist::INIT_SPACE,
+        seeds = [DEX_ALLOWLIST_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub dex_allowlist: Account<'info, DexAllowlist>,
+    
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageDexAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [DEX_ALLOWLIST_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub dex_allowlist: Account<'info, DexAllowlist>,
+    
+    #[account(
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = !vault.multisig_enabled() @ VaultError::MultisigRequired
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestFees<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub keeper_authority: Signer<'info>,
+    
+    /// CHECK: Token mint, parsed by Token-2022
+    #[account(mut, address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeHistory<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + FeeRateHistory::INIT_SPACE,
+        seeds = [FEE_HISTORY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeRateHistory>,
+    
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferFee<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = !vault.multisig_enabled() @ VaultError::MultisigRequired
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Token mint, updated by Token-2022
+    #[account(mut, address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_HISTORY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeRateHistory>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSchedule<'info> {
+    #[account(
+        mut,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = !vault.multisig_enabled() @ VaultError::MultisigRequired
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankFeeSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    /// CHECK: Token mint, updated by Token-2022
+    #[account(mut, address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_HISTORY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub fee_history: Account<'info, FeeRateHistory>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SyncTransferFee<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    /// CHECK: Token mint, parsed by Token-2022
+    #[account(address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncPendingWithheld<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub keeper_authority: Signer<'info>,
+    
+    /// CHECK: Token mint, parsed by Token-2022
+    #[account(address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFeesFromMint<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub keeper_authority: Signer<'info>,
+    
+    /// Vault authority, co-signs to withdraw below the harvest threshold
+    pub authority: Option<Signer<'info>>,
+    
+    /// CHECK: Token mint, parsed by Token-2022
+    #[account(mut, address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = vault_token_address(&vault.key(), &vault.token_mint) @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct SplitHarvestedFees<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+    
+    #[account(address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        address = vault_token_address(&vault.key(), &vault.token_mint) @ VaultError::InvalidVaultTokenAccount
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == vault.owner_wallet @ VaultError::InvalidOwnerTokenAccount,
+        constraint = owner_token_account.mint == vault.token_mint @ VaultError::InvalidOwnerTokenAccount
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = keeper_authority,
+        seeds = [HOLDER_TREASURY_SEED, vault.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = vault,
+        token::token_program = token_program
+    )]
+    pub holder_treasury: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Receives the keeper allowance, only needed when one is taken
+    #[account(
+        mut,
+        constraint = keeper_token_account.mint == vault.token_mint @ VaultError::InvalidKeeperTokenAccount,
+        constraint = keeper_token_account.owner == keeper_authority.key() @ VaultError::InvalidKeeperTokenAccount
+    )]
+    pub keeper_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawHarvestedFees<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub keeper_authority: Signer<'info>,
+    
+    #[account(address = vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [HOLDER_TREASURY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub holder_treasury: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = keeper_token_account.mint == vault.token_mint @ VaultError::InvalidKeeperTokenAccount,
+        constraint = keeper_token_account.owner == keeper_authority.key() @ VaultError::InvalidKeeperTokenAccount
+    )]
+    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(merkle_root: [u8; 32], total_amount: u64, recipient_count: u32)]
+pub struct CreateDistributionEpoch<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+    
+    /// CHECK: Smart Dial state, address pinned in vault config and decoded manually
+    #[account(address = vault.reward_dial @ VaultError::InvalidRewardDial)]
+    pub reward_dial: UncheckedAccount<'info>,
+    
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = keeper_authority,
+        space = DistributionEpoch::space(recipient_count),
+        seeds = [
+            DISTRIBUTION_EPOCH_SEED,
+            vault.key().as_ref(),
+            &vault.distribution_epoch_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub distribution_epoch: Box<Account<'info, DistributionEpoch>>,
+    
+    #[account(
+        init,
+        payer = keeper_authority,
+        seeds = [EPOCH_ESCROW_SEED, distribution_epoch.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = vault,
+        token::token_program = token_program
+    )]
+    pub epoch_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = keeper_reward_account.mint == reward_mint.key() @ VaultError::RewardMintMismatch
+    )]
+    pub keeper_reward_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    #[account(
+        mut,
+        seeds = [
+            DISTRIBUTION_EPOCH_SEED,
+            vault.key().as_ref(),
+            &distribution_epoch.epoch.to_le_bytes()
+        ],
+        bump
+    )]
+    pub distribution_epoch: Box<Account<'info, DistributionEpoch>>,
+    
+    #[account(
+        mut,
+        seeds = [EPOCH_ESCROW_SEED, distribution_epoch.key().as_ref()],
+        bump
+    )]
+    pub epoch_escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        address = distribution_epoch.reward_mint @ VaultError::RewardMintMismatch,
+        mint::token_program = token_program
+    )]
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    pub claimant: Signer<'info>,
+    
+    #[account(
+        mut,
+        constraint = claimant_token_account.mint == distribution_epoch.reward_mint @ VaultError::RewardMintMismatch,
+        constraint = claimant_token_account.owner == claimant.key() @ VaultError::Unauthorized
+    )]
+    pub claimant_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct LogKeeperWork<'info> {
+    #[account(
+        mut,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(
+        init_if_needed,
+        payer = keeper_authority,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref(), &vault.keeper_log_page().to_le_bytes()],
+        bump,
+        space = 8 + KeeperWorkLogPage::INIT_SPACE
+    )]
+    pub keeper_work_log_page: Account<'info, KeeperWorkLogPage>,
+    
+    #[account(mut)]
+    pub keeper_authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(action: ExclusionAction, wallet: Pubkey)]
+pub struct ManageExclusions<'info> {
+    #[account(
+        mut,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = !vault.multisig_enabled() @ VaultError::MultisigRequired
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    /// CHECK: Exclusion marker PDA, created or closed by the handler
+    #[account(
+        mut,
+        seeds = [EXCLUSION_SEED, vault.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub exclusion_marker: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateExclusions<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// CHECK: Vault in any layout version, checked by the handler
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, token_mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Token mint, only used for the vault seeds
+    pub token_mint: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePoolRegistry<'info> {
+    /// CHECK: Pool registry page 0 in any layout version, checked by the handler
+    #[account(
+        mut,
+        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub pool_registry: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateKeeperWorkLog<'info> {
+    /// CHECK: Pre-paging keeper work log in any layout version, checked by the handler
+    #[account(
+        mut,
+        seeds = [KEEPER_LOG_SEED, vault.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub keeper_work_log: UncheckedAccount<'info>,
+    
+    #[account(
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct IsExcluded<'info> {
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+    
+    /// CHECK: Exclusion marker PDA, may not exist
+    #[account(
+        seeds = [EXCLUSION_SEED, vault.key().as_ref(), wallet.as_ref()],
+        bump
+    )]
+    pub exclusion_marker: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = !vault.multisig_enabled() @ VaultError::MultisigRequired
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Account<'info, VaultState>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.is_admin(&admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    /// Authority, or any admin signer when a signer set is enabled
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [
+            PENDING_CONFIG_SEED,
+            vault.key().as_ref(),
+            &vault.config_change_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CONFIG_SEED,
+            vault.key().as_ref(),
+            &pending_change.id.to_le_bytes()
+        ],
+        bump,
+        close = proposer
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+    
+    /// CHECK: Receives the pending change rent
+    #[account(mut, address = pending_change.proposer @ VaultError::Unauthorized)]
+    pub proposer: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub executor: Signer<'info>,
+    
+    /// CHECK: Token mint, checked against the vault and parsed by Token-2022
+    #[account(mut)]
+    pub token_mint: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub vault_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(mut)]
+    pub destination_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [HOLDER_TREASURY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub holder_treasury: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    
+    pub token_program: Option<Program<'info, Token2022>>,
+    
+    #[account(
+        mut,
+        seeds = [DEX_ALLOWLIST_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub dex_allowlist: Option<Box<Account<'info, DexAllowlist>>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_HISTORY_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub fee_history: Option<Box<Account<'info, FeeRateHistory>>>,
+    
+    /// CHECK: Exclusion marker PDA, derived from the queued wallet in the handler
+    #[account(mut)]
+    pub exclusion_marker: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Receives the rent of removed exclusion markers
+    #[account(mut, address = vault.authority @ VaultError::Unauthorized)]
+    pub authority: Option<UncheckedAccount<'info>>,
+    
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveConfigChange<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.multisig_enabled() @ VaultError::MultisigNotEnabled,
+        constraint = vault.is_admin(&signer.key()) @ VaultError::Unauthorized
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    pub signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CONFIG_SEED,
+            vault.key().as_ref(),
+            &pending_change.id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
+        bump,
+        constraint = vault.version == VAULT_STATE_VERSION @ VaultError::AccountNotMigrated,
+        constraint = vault.is_admin(&admin.key()) @ VaultError::Unauthorized
+    )]
+    pub vault: Box<Account<'info, VaultState>>,
+    
+    /// Authority, or the proposing signer when a signer set is enabled
+    #[account(
+        constraint = !vault.multisig_enabled() || admin.key() == pending_change.proposer @ VaultError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [
+            PENDING_CONFIG_SEED,
+            vault.key().as_ref(),
+            &pending_change.id.to_le_bytes()
+        ],
+        bump,
+        close = proposer
+    )]
+    pub pending_change: Box<Account<'info, PendingConfigChange>>,
+    
+    /// CHECK: Receives the pending change rent
+    #[account(mut, address = pending_change.proposer @ VaultError::Unauthorized)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+// State structures
+
+#[account]
+#[derive(InitSpace, Default)]
+pub struct VaultState {
+    pub version: u8, // Right after the discriminator, VAULT_STATE_VERSION once migrated
+    pub authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub min_hold_amount: u64,
+    #[max_len(100)]
+    pub reward_exclusions: Vec<Pubkey>, // Legacy list, drained into markers by migrate_exclusions
+    pub harvest_threshold: u64,
+    pub total_fees_harvested: u64,
+    pub pending_withheld: u64,
+    pub last_harvest_time: i64,
+    pub last_harvest_amount: u64,
+    pub last_distribution_time: i64,
+    pub launch_timestamp: i64,
+    pub owner_tax_share: u64,
+    pub holders_tax_share: u64,
+    pub distribution_epoch_count: u64,
+    pub reward_dial: Pubkey,
+    pub pending_authority: Pubkey,
+    pub pending_keeper_authority: Pubkey,
+    pub config_delay: i64,
+    pub emergency_delay: i64,
+    pub config_change_count: u64,
+    #[max_len(10)]
+    pub admin_signers: Vec<Pubkey>,
+    pub admin_threshold: u8,
+    pub withdraw_window: i64,
+    pub max_withdraw_per_window: u64,
+    pub max_withdraw_bps: u16,
+    pub window_start: i64,      // Start of the newest withdraw bucket
+    pub window_withdrawn: u64,  // Total over withdraw_buckets
+    pub ledger: FeeLedger,
+    pub keeper_log_count: u64, // next keeper work log sequence number
+    pub pool_registry_pages: u16,
+    pub require_verified_pools: bool, // Pools can only be added through add_verified_pool
+    pub exclusion_count: u64,         // Live exclusion markers
+    pub fee_bps_cap: u16,             // Upper bound for set_transfer_fee
+    pub max_fee_cap: u64,
+    pub transfer_fee: TransferFeeRates,
+    #[max_len(8)]
+    pub fee_schedule: Vec<FeeStep>,       // Launch tax steps, applied by crank_fee_schedule
+    pub active_fee_step: Option<u8>,      // Step in effect on the mint
+    pub require_launch_pool: bool, // set_launch_time must prove a registered pool exists
+    pub pending_fee_step: Option<u8>, // Step set on the mint, in effect from transfer_fee.pending_epoch
+    pub withdraw_buckets: [u64; WITHDRAW_BUCKETS], // Keeper withdrawals per half window, newest first
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct FeeStep {
+    pub starts_after: i64, // Seconds after launch
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+// Mint transfer fee as last seen by the vault, a new rate stays pending for two epochs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct TransferFeeRates {
+    pub active_basis_points: u16,
+    pub active_maximum_fee: u64,
+    pub pending_basis_points: u16,
+    pub pending_maximum_fee: u64,
+    pub pending_epoch: u64, // 0 when no change is pending
+}
+
+// Vault layout before versioning
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct VaultStateV0 {
+    pub authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub token_mint: Pubkey,
+    pub min_hold_amount: u64,
+    #[max_len(100)]
+    pub reward_exclusions: Vec<Pubkey>,
+    pub harvest_threshold: u64,
+    pub total_fees_harvested: u64,
+    pub total_rewards_distributed: u64,
+    pub pending_withheld: u64,
+    pub last_harvest_time: i64,
+    pub last_harvest_amount: u64,
+    pub last_distribution_time: i64,
+    pub launch_timestamp: i64,
+}
+
+// The baseline layout is told apart by size alone
+const _: () = assert!(VaultState::INIT_SPACE != VaultStateV0::INIT_SPACE);
+
+// Where harvested MIKO currently sits, every movement is checked
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct FeeLedger {
+    pub harvested: u64,        // In the vault token account, not yet split
+    pub owed_to_owner: u64,    // Owner share allocated but not yet transferred
+    pub owed_to_holders: u64,  // Holder share in the holder treasury
+    pub keeper_custody: u64,   // Withdrawn to the keeper, not yet logged as distributed
+    pub paid_out: u64,         // Transferred to its final recipient
+}
+
+impl VaultState {
+    // Decode a vault in the current layout or the baseline one it replaces
+    // V0 predates the version byte and is recognised by its fixed size, any other layout
+    // is picked by the version byte stored right after the discriminator
+    pub fn decode_any(data: &[u8]) -> Result<(Self, u8)> {
+        require!(
+            data.len() > 8 && &data[..8] == VaultState::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        
+        if data.len() == 8 + VaultStateV0::INIT_SPACE {
+            let legacy = VaultStateV0::deserialize(&mut &data[8..])?;
+            return Ok((VaultState::from_v0(legacy), 0));
+        }
+        require!(data[8] == VAULT_STATE_VERSION, VaultError::UnsupportedVersion);
+        Ok((VaultState::try_deserialize(&mut &data[..])?, VAULT_STATE_VERSION))
+    }
+    
+    // Legacy fields carry over, everything added since takes its initialize default
+    pub fn from_v0(legacy: VaultStateV0) -> Self {
+        VaultState {
+            authority: legacy.authority,
+            keeper_authority: legacy.keeper_authority,
+            owner_wallet: legacy.owner_wallet,
+            token_mint: legacy.token_mint,
+            min_hold_amount: legacy.min_hold_amount,
+            reward_exclusions: legacy.reward_exclusions,
+            harvest_threshold: legacy.harvest_threshold,
+            total_fees_harvested: legacy.total_fees_harvested,
+            pending_withheld: legacy.pending_withheld,
+            last_harvest_time: legacy.last_harvest_time,
+            last_harvest_amount: legacy.last_harvest_amount,
+            last_distribution_time: legacy.last_distribution_time,
+            launch_timestamp: legacy.launch_timestamp,
+            owner_tax_share: OWNER_TAX_SHARE,
+            holders_tax_share: HOLDERS_TAX_SHARE,
+            config_delay: DEFAULT_CONFIG_DELAY,
+            emergency_delay: DEFAULT_EMERGENCY_DELAY,
+            withdraw_window: DEFAULT_WITHDRAW_WINDOW,
+            max_withdraw_per_window: DEFAULT_MAX_WITHDRAW_PER_WINDOW,
+            max_withdraw_bps: BPS_DENOMINATOR as u16,
+            fee_bps_cap: DEFAULT_FEE_BPS_CAP,
+            max_fee_cap: u64::MAX,
+            version: VAULT_STATE_VERSION,
+            ..Default::default()
+        }
+    }
+    
+    pub fn keeper_log_page(&self) -> u64 {
+        self.keeper_log_count / KEEPER_LOG_PAGE_SIZE
+    }
+    
+    pub fn multisig_enabled(&self) -> bool {
+        self.admin_threshold > 0
+    }
+    
+    // Admin is the signer set when enabled, otherwise the single authority
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        if self.multisig_enabled() {
+            self.admin_signers.contains(key)
+        } else {
+            self.authority == *key
+        }
+    }
+    
+    // Only approvals from the current signer set count
+    pub fn approval_count(&self, approvals: &[Pubkey]) -> u8 {
+        approvals.iter().filter(|a| self.admin_signers.contains(a)).count() as u8
+    }
+    
+    // Apply both keeper withdrawal limits over the rolling window
+    // Withdrawals are counted per half-window bucket, so each one counts for 1 to 1.5 windows
+    pub fn record_withdrawal(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.roll_withdraw_buckets(now)?;
+        
+        let window_withdrawn = self.window_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        require!(
+            window_withdrawn <= self.max_withdraw_per_window,
+            VaultError::WithdrawRateLimitExceeded
+        );
+        
+        let withdrawable = (self.ledger.owed_to_holders as u128)
+            .checked_mul(self.max_withdraw_bps as u128)
+            .and_then(|v| v.checked_div(BPS_DENOMINATOR as u128))
+            .ok_or(VaultError::MathOverflow)? as u64;
+        require!(
+            amount <= withdrawable,
+            VaultError::WithdrawExceedsUnwithdrawnFees
+        );
+        
+        self.withdraw_buckets[0] = self.withdraw_buckets[0]
+            .checked_add(amount)
+            .ok_or(VaultError::MathOverflow)?;
+        self.window_withdrawn = window_withdrawn;
+        ledger_debit(&mut self.ledger.owed_to_holders, amount)
+    }
+    
+    // Age the buckets up to the one holding `now`, buckets past the window drop out
+    fn roll_withdraw_buckets(&mut self, now: i64) -> Result<()> {
+        let bucket_len = (self.withdraw_window / 2 + self.withdraw_window % 2).max(1);
+        let bucket_start = now.div_euclid(bucket_len) * bucket_len;
+        let shift = bucket_start
+            .saturating_sub(self.window_start)
+            .div_euclid(bucket_len)
+            .min(WITHDRAW_BUCKETS as i64) as usize;
+        if shift == 0 {
+            return Ok(());
+        }
+        
+        self.withdraw_buckets.rotate_right(shift);
+        self.withdraw_buckets[..shift].fill(0);
+        self.window_start = bucket_start;
+        self.window_withdrawn = self.withdraw_buckets
+            .iter()
+            .try_fold(0u64, |total, bucket| total.checked_add(*bucket))
+            .ok_or(VaultError::MathOverflow)?;
+        Ok(())
+    }
+    
+    // Token balances must cover their buckets; anything above is untracked deposits
+    pub fn reconcile_ledger(
+        &self,
+        vault_balance: Option<u64>,
+        treasury_balance: Option<u64>,
+        keeper_balance: Option<u64>,
+    ) -> Result<()> {
+        if let Some(balance) = vault_balance {
+            require!(
+                balance >= self.ledger.harvested.checked_add(self.ledger.owed_to_owner).ok_or(VaultError::MathOverflow)?,
+                VaultError::LedgerMismatch
+            );
+        }
+        if let Some(balance) = treasury_balance {
+            require!(
+                balance >= self.ledger.owed_to_holders,
+                VaultError::LedgerMismatch
+            );
+        }
+        if let Some(balance) = keeper_balance {
+            require!(
+                balance >= self.ledger.keeper_custody,
+                VaultError::LedgerMismatch
+            );
+        }
+        Ok(())
+    }
+    
+    pub fn pending_for(&self, role: AuthorityRole) -> Pubkey {
+        match role {
+            AuthorityRole::Authority => self.pending_authority,
+            AuthorityRole::Keeper => self.pending_keeper_authority,
+        }
+    }
+    
+    // Keeper and authority must always be different keys
+    pub fn require_distinct_roles(&self, role: AuthorityRole, candidate: Pubkey) -> Result<()> {
+        let other = match role {
+            AuthorityRole::Authority => self.keeper_authority,
+            AuthorityRole::Keeper => self.authority,
+        };
+        require_keys_neq!(candidate, other, VaultError::KeeperMatchesAuthority);
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingConfigChange {
+    pub vault: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ConfigChange,
+    pub queued_at: i64,
+    pub execute_after: i64,
+    #[max_len(10)]
+    pub approvals: Vec<Pubkey>,
+    pub required_approvals: u8, // Signer threshold when queued, 0 under a single authority
+}
+
+impl PendingConfigChange {
+    // The stricter of the threshold at queue time and the current one
+    pub fn approvals_needed(&self, vault: &VaultState) -> u8 {
+        self.required_approvals.max(vault.admin_threshold)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, InitSpace)]
+pub struct ConfigUpdate {
+    pub owner_wallet: Option<Pubkey>,
+    pub min_hold_amount: Option<u64>,
+    pub harvest_threshold: Option<u64>,
+    pub owner_tax_share: Option<u64>,
+    pub holders_tax_share: Option<u64>,
+    pub reward_dial: Option<Pubkey>,
+    pub config_delay: Option<i64>,
+    pub emergency_delay: Option<i64>,
+    pub withdraw_window: Option<i64>,
+    pub max_withdraw_per_window: Option<u64>,
+    pub max_withdraw_bps: Option<u16>,
+    pub require_verified_pools: Option<bool>,
+    pub fee_bps_cap: Option<u16>,
+    pub max_fee_cap: Option<u64>,
+    pub require_launch_pool: Option<bool>,
+}
+
+impl ConfigUpdate {
+    pub fn is_timelocked(&self) -> bool {
+        self.owner_wallet.is_some() || self.min_hold_amount.is_some() || self.harvest_threshold.is_some()
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DistributionEpoch {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub reward_mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub recipient_count: u32,
+    pub created_at: i64,
+    #[max_len(0)] // Sized per epoch, see `space`
+    pub claimed_bitmap: Vec<u8>,
+}
+
+impl DistributionEpoch {
+    pub fn bitmap_len(recipient_count: u32) -> usize {
+        (recipient_count as usize).div_ceil(8)
+    }
+    
+    pub fn space(recipient_count: u32) -> usize {
+        8 + Self::INIT_SPACE + Self::bitmap_len(recipient_count)
+    }
+    
+    pub fn is_claimed(&self, index: u32) -> bool {
+        self.claimed_bitmap[index as usize / 8] & (1 << (index % 8)) != 0
+    }
+    
+    pub fn set_claimed(&mut self, index: u32) {
+        self.claimed_bitmap[index as usize / 8] |= 1 << (index % 8);
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct FeeRateHistory {
+    pub vault: Pubkey,
+    #[max_len(MAX_FEE_HISTORY)]
+    pub changes: Vec<FeeRateChange>,
+}
+
+impl FeeRateHistory {
+    pub fn record(&mut self, change: FeeRateChange) {
+        if self.changes.len() >= MAX_FEE_HISTORY {
+            self.changes.remove(0);
+        }
+        self.changes.push(change);
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct FeeRateChange {
+    pub changed_at: i64,
+    pub effective_epoch: u64,
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+// Keeper work log from before paging, kept read-only
+#[account]
+#[derive(InitSpace)]
+pub struct KeeperWorkLog {
+    pub version: u8, // Right after the discriminator
+    pub vault: Pubkey,
+    #[max_len(50)]
+    pub entries: Vec<LegacyKeeperWorkEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct KeeperWorkLogV0 {
+    pub vault: Pubkey,
+    pub entries: Vec<LegacyKeeperWorkEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct LegacyKeeperWorkEntry {
+    pub timestamp: i64,
+    pub work_type: KeeperWorkType,
+    pub amount: u64,
+    #[max_len(100)]
+    pub details: String,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct KeeperWorkLogPage {
+    pub vault: Pubkey,
+    pub page: u64,
+    #[max_len(KEEPER_LOG_PAGE_SIZE)]
+    pub entries: Vec<KeeperWorkEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct KeeperWorkEntry {
+    pub sequence: u64,
+    pub timestamp: i64,
+    pub work: KeeperWork,
+}
+
+// Typed payload for each kind of keeper work
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum KeeperWork {
+    HarvestFees {
+        accounts: u32,
+        amount: u64,
+    },
+    SwapToRewardToken {
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        input_amount: u64,
+        output_amount: u64,
+        route_id: [u8; 32],
+    },
+    DistributeToOwner {
+        mint: Pubkey,
+        recipient: Pubkey,
+        input_amount: u64, // MIKO taken from keeper custody
+        amount: u64,
+    },
+    DistributeToHolders {
+        reward_mint: Pubkey,
+        epoch: u64,
+        recipient_count: u32,
+        input_amount: u64, // MIKO taken from keeper custody
+        amount: u64,
+    },
+    KeeperTopUp {
+        input_amount: u64,
+        output_lamports: u64,
+    },
+}
+
+impl KeeperWork {
+    pub fn work_type(&self) -> KeeperWorkType {
+        match self {
+            KeeperWork::HarvestFees { .. } => KeeperWorkType::HarvestFees,
+            KeeperWork::SwapToRewardToken { .. } => KeeperWorkType::SwapToRewardToken,
+            KeeperWork::DistributeToOwner { .. } => KeeperWorkType::DistributeToOwner,
+            KeeperWork::DistributeToHolders { .. } => KeeperWorkType::DistributeToHolders,
+            KeeperWork::KeeperTopUp { .. } => KeeperWorkType::KeeperTopUp,
+        }
+    }
+    
+    // MIKO leaving keeper custody; swaps are intermediate and counted when distributed
+    pub fn custody_spent(&self) -> u64 {
+        match self {
+            KeeperWork::DistributeToOwner { input_amount, .. }
+            | KeeperWork::DistributeToHolders { input_amount, .. }
+            | KeeperWork::KeeperTopUp { input_amount, .. } => *input_amount,
+            KeeperWork::HarvestFees { .. } | KeeperWork::SwapToRewardToken { .. } => 0,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PoolRegistry {
+    pub version: u8, // Right after the discriminator
+    pub vault: Pubkey,
+    pub page: u16,
+    #[max_len(MAX_POOLS)]
+    pub pools: Vec<PoolEntry>,
+}
+
+// Pool registry layout before versioning
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolRegistryV0 {
+    pub vault: Pubkey,
+    pub pools: Vec<Pubkey>,
+}
+
+impl PoolRegistry {
+    // Page 0 as it was before versioning, every pool with only its address known
+    pub fn from_v0(legacy: PoolRegistryV0, added_at: i64) -> Self {
+        PoolRegistry {
+            version: POOL_REGISTRY_VERSION,
+            vault: legacy.vault,
+            page: 0,
+            pools: legacy.pools
+                .into_iter()
+                .map(|pool| PoolEntry {
+                    pool,
+                    dex_program: Pubkey::default(),
+                    token_vault: pool,
+                    quote_mint: Pubkey::default(),
+                    added_at,
+                })
+                .collect(),
+        }
+    }
+    
+    pub fn add(&mut self, info: PoolInfo, added_at: i64) -> Result<()> {
+        require!(
+            !self.pools.iter().any(|entry| entry.pool == info.pool),
+            VaultError::PoolAlreadyRegistered
+        );
+        require!(self.pools.len() < MAX_POOLS, VaultError::PoolRegistryFull);
+        
+        self.pools.push(PoolEntry {
+            pool: info.pool,
+            dex_program: info.dex_program,
+            token_vault: info.token_vault,
+            quote_mint: info.quote_mint,
+            added_at,
+        });
+        Ok(())
+    }
+}
+
+// Pool details supplied by the keeper
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PoolInfo {
+    pub pool: Pubkey,
+    pub dex_program: Pubkey,
+    pub token_vault: Pubkey, // Pool's MIKO vault account, excluded from rewards
+    pub quote_mint: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct PoolEntry {
+    pub pool: Pubkey,
+    pub dex_program: Pubkey,
+    pub token_vault: Pubkey,
+    pub quote_mint: Pubkey,
+    pub added_at: i64,
+}
+
+// One per excluded wallet, seeded by vault and wallet
+#[account]
+#[derive(InitSpace)]
+pub struct ExclusionMarker {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+    pub reason: ExclusionReason,
+    pub excluded_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DexAllowlist {
+    pub vault: Pubkey,
+    #[max_len(MAX_DEX_PROGRAMS)]
+    pub programs: Vec<DexProgram>,
+}
+
+impl DexAllowlist {
+    pub fn find(&self, program_id: &Pubkey) -> Option<DexProgram> {
+        self.programs.iter().find(|dex| dex.program_id == *program_id).copied()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct DexProgram {
+    pub program_id: Pubkey,
+    pub kind: DexKind,
+}
+
+// Enums
+
+// Pool layouts the vault knows how to decode
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum DexKind {
+    RaydiumCpmm,
+    OrcaWhirlpool,
+}
+
+impl DexKind {
+    // Anchor account name and (mint, vault) offsets of both pool sides
+    fn layout(&self) -> (&'static str, [(usize, usize); 2]) {
+        match self {
+            DexKind::RaydiumCpmm => ("PoolState", [(168, 72), (200, 104)]),
+            DexKind::OrcaWhirlpool => ("Whirlpool", [(101, 133), (181, 213)]),
+        }
+    }
+    
+    // Returns (mint, token vault) for each side of the pool
+    pub fn decode_pool(&self, data: &[u8]) -> Result<[(Pubkey, Pubkey); 2]> {
+        let (name, offsets) = self.layout();
+        let discriminator = hashv(&[format!("account:{}", name).as_bytes()]).to_bytes();
+        require!(
+            data.len() >= 8 && data[..8] == discriminator[..8],
+            VaultError::InvalidPoolAccount
+        );
+        
+        let read = |offset: usize| -> Result<Pubkey> {
+            let bytes = data
+                .get(offset..offset + 32)
+                .ok_or(VaultError::InvalidPoolAccount)?;
+            Ok(Pubkey::try_from(bytes).unwrap())
+        };
+        Ok([
+            (read(offsets[0].0)?, read(offsets[0].1)?),
+            (read(offsets[1].0)?, read(offsets[1].1)?),
+        ])
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ExclusionReason {
+    System,
+    Pool,
+    Protocol,
+    Manual,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AllowlistAction {
+    Add,
+    Remove,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ExclusionAction {
+    Add,
+    Remove,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub enum ConfigChange {
+    UpdateConfig { update: ConfigUpdate },
+    EmergencyWithdraw { amount: u64, destination: Pubkey },
+    EmergencyWithdrawWithheld { destination: Pubkey },
+    ManageExclusion { action: ExclusionAction, wallet: Pubkey, reason: ExclusionReason },
+    ManageDexAllowlist { action: AllowlistAction, dex: DexProgram },
+    SetTransferFee { basis_points: u16, maximum_fee: u64 },
+    SetFeeSchedule {
+        #[max_len(8)]
+        steps: Vec<FeeStep>,
+    },
+    SetAdminSigners {
+        #[max_len(10)]
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    ProposeKeeperAuthority { keeper: Pubkey },
+    EmergencyWithdrawTreasury { amount: u64, destination: Pubkey },
+    ProposeAuthority { authority: Pubkey },
+}
+
+impl ConfigChange {
+    // Only changes that redirect or gate holder value sit behind the timelock
+    pub fn delay(&self, vault: &VaultState) -> i64 {
+        match self {
+            ConfigChange::UpdateConfig { update } if update.is_timelocked() => vault.config_delay,
+            ConfigChange::EmergencyWithdraw { .. }
+            | ConfigChange::EmergencyWithdrawWithheld { .. }
+            | ConfigChange::EmergencyWithdrawTreasury { .. } => vault.emergency_delay,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthorityRole {
+    Authority,
+    Keeper,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum KeeperWorkType {
+    HarvestFees,
+    SwapToRewardToken,
+    DistributeToOwner,
+    DistributeToHolders,
+    KeeperTopUp,
+}
+
+// Events
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccountHarvest {
+    pub account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesHarvested {
+    pub vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub harvested: Vec<AccountHarvest>,
+    pub skipped: Vec<Pubkey>,
+    pub total: u64,
+    pub pending_withheld: u64,
+}
+
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub keeper_authority: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub min_hold_amount: u64,
+    pub harvest_threshold: u64,
+    pub exclusion_count: u64,
+}
+
+#[event]
+pub struct LaunchTimeSet {
+    pub vault: Pubkey,
+    pub launch_timestamp: i64,
+    pub set_by: Pubkey,
+    pub pool: Option<Pubkey>,
+}
+
+#[event]
+pub struct FeesWithdrawnFromMint {
+    pub vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub amount: u64,
+    pub threshold_overridden: bool,
+    pub total_fees_harvested: u64,
+    pub pending_withheld: u64,
+    pub ledger: FeeLedger,
+}
+
+#[event]
+pub struct HarvestedFeesSplit {
+    pub vault: Pubkey,
+    pub owner_token_account: Pubkey,
+    pub holder_treasury: Pubkey,
+    pub amount: u64,
+    pub owner_amount: u64,
+    pub keeper_allowance: u64,
+    pub holders_amount: u64,
+    pub ledger: FeeLedger,
+}
+
+#[event]
+pub struct FeesWithdrawnToKeeper {
+    pub vault: Pubkey,
+    pub keeper_token_account: Pubkey,
+    pub amount: u64,
+    pub received: u64,
+    pub window_start: i64,
+    pub window_withdrawn: u64,
+    pub ledger: FeeLedger,
+}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + VaultState::INIT_SPACE,
-        seeds = [VAULT_SEED, token_mint.key().as_ref()],
-        bump
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Token mint
-    pub token_mint: UncheckedAccount<'info>,
-    
-    /// CHECK: Vault program ID
-    pub vault_program: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct ExclusionUpdated {
+    pub vault: Pubkey,
+    pub wallet: Pubkey,
+    pub action: ExclusionAction,
+    pub reason: ExclusionReason,
+    pub exclusion_count: u64,
+    pub legacy_exclusions: u32,
 }
 
-#[derive(Accounts)]
-pub struct InitializePoolRegistry<'info> {
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + PoolRegistry::INIT_SPACE,
-        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
-        bump
-    )]
-    pub pool_registry: Account<'info, PoolRegistry>,
-    
-    pub vault: Account<'info, VaultState>,
-    
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct ConfigUpdated {
+    pub vault: Pubkey,
+    pub owner_wallet: Pubkey,
+    pub min_hold_amount: u64,
+    pub harvest_threshold: u64,
+    pub owner_tax_share: u64,
+    pub holders_tax_share: u64,
+    pub reward_dial: Pubkey,
+    pub config_delay: i64,
+    pub emergency_delay: i64,
+    pub withdraw_window: i64,
+    pub max_withdraw_per_window: u64,
+    pub max_withdraw_bps: u16,
+    pub require_verified_pools: bool,
+    pub fee_bps_cap: u16,
+    pub max_fee_cap: u64,
+    pub require_launch_pool: bool,
 }
 
-#[derive(Accounts)]
-pub struct SetLaunchTime<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, VaultState>,
+#[event]
+pub struct TransferFeeSet {
+    pub vault: Pubkey,
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub effective_epoch: u64,
+    pub active_basis_points: u16,
+    pub active_maximum_fee: u64,
 }
 
-#[derive(Accounts)]
-pub struct UpdatePoolRegistry<'info> {
-    #[account(
-        mut,
-        seeds = [POOL_REGISTRY_SEED, vault.key().as_ref()],
-        bump
-    )]
-    pub pool_registry: Account<'info, PoolRegistry>,
-    
-    #[account(
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub keeper_authority: Signer<'info>,
+#[event]
+pub struct FeeScheduleSet {
+    pub vault: Pubkey,
+    pub steps: Vec<FeeStep>,
 }
 
-#[derive(Accounts)]
-pub struct HarvestFees<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub keeper_authority: Signer<'info>,
-    
-    /// CHECK: Token mint
-    #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
-    
-    pub token_program: Program<'info, Token2022>,
+#[event]
+pub struct FeeStepApplied {
+    pub vault: Pubkey,
+    pub step: u8,
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub elapsed: i64,
+    pub effective_epoch: u64,
 }
 
-#[derive(Accounts)]
-pub struct WithdrawFeesFromMint<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub keeper_authority: Signer<'info>,
-    
-    /// CHECK: Token mint
-    #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token2022>,
+#[event]
+pub struct FeeStepActivated {
+    pub vault: Pubkey,
+    pub step: u8,
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub epoch: u64,
 }
 
-#[derive(Accounts)]
-pub struct WithdrawHarvestedFees<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub keeper_authority: Signer<'info>,
-    
-    /// CHECK: Token mint
-    pub token_mint: InterfaceAccount<'info, Mint>,
-    
-    #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    pub token_program: Interface<'info, TokenInterface>,
+#[event]
+pub struct AdminSignersUpdated {
+    pub vault: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
 }
 
-#[derive(Accounts)]
-pub struct LogKeeperWork<'info> {
-    #[account(
-        init_if_needed,
-        payer = keeper_authority,
-        seeds = [b"keeper_log", vault.key().as_ref()],
-        bump,
-        space = 8 + KeeperWorkLog::INIT_SPACE
-    )]
-    pub keeper_work_log: Account<'info, KeeperWorkLog>,
-    
-    #[account(
-        constraint = vault.keeper_authority == keeper_authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    #[account(mut)]
-    pub keeper_authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct EmergencyWithdrawn {
+    pub vault: Pubkey,
+    pub source: Pubkey, // Vault token account or holder treasury
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub ledger: FeeLedger,
 }
 
-#[derive(Accounts)]
-pub struct ManageExclusions<'info> {
-    #[account(
-        mut,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub authority: Signer<'info>,
+#[event]
+pub struct WithheldEmergencyWithdrawn {
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub withdrawn: Vec<AccountHarvest>,
+    pub skipped: Vec<Pubkey>,
+    pub total: u64,
 }
 
-#[derive(Accounts)]
-pub struct UpdateConfig<'info> {
-    #[account(
-        mut,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub authority: Signer<'info>,
+#[event]
+pub struct DistributionEpochCreated {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub reward_mint: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub recipient_count: u32,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyWithdraw<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub authority: Signer<'info>,
-    
-    #[account(mut)]
-    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    /// CHECK: Token mint
-    pub token_mint: InterfaceAccount<'info, Mint>,
-    
-    pub token_program: Interface<'info, TokenInterface>,
+#[event]
+pub struct RewardClaimed {
+    pub vault: Pubkey,
+    pub epoch: u64,
+    pub claimant: Pubkey,
+    pub index: u32,
+    pub amount: u64,
 }
 
-#[derive(Accounts)]
-pub struct EmergencyWithdrawWithheld<'info> {
-    #[account(
-        mut,
-        seeds = [VAULT_SEED, vault.token_mint.as_ref()],
-        bump,
-        constraint = vault.authority == authority.key() @ VaultError::Unauthorized
-    )]
-    pub vault: Account<'info, VaultState>,
-    
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Token mint
-    #[account(mut)]
-    pub token_mint: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token2022>,
+#[event]
+pub struct PoolRegistryUpdated {
+    pub vault: Pubkey,
+    pub page: u16,
+    pub added: Vec<Pubkey>,
+    pub removed: Vec<Pubkey>,
+    pub total_pools: u32,
 }
 
-// State structures
+#[event]
+pub struct DexAllowlistUpdated {
+    pub vault: Pubkey,
+    pub action: AllowlistAction,
+    pub dex: DexProgram,
+    pub total_programs: u32,
+}
 
-#[account]
-#[derive(InitSpace)]
-pub struct VaultState {
-    pub authority: Pubkey,
-    pub keeper_authority: Pubkey,
-    pub owner_wallet: Pubkey,
-    pub token_mint: Pubkey,
-    pub min_hold_amount: u64,
-    #[max_len(100)]
-    pub reward_exclusions: Vec<Pubkey>,
-    pub harvest_threshold: u64,
-    pub total_fees_harvested: u64,
-    pub total_rewards_distributed: u64,
-    pub pending_withheld: u64,
-    pub last_harvest_time: i64,
-    pub last_harvest_amount: u64,
-    pub last_distribution_time: i64,
-    pub launch_timestamp: i64,
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct KeeperWorkLog {
+#[event]
+pub struct KeeperWorkLogged {
     pub vault: Pubkey,
-    #[max_len(50)]
-    pub entries: Vec<KeeperWorkEntry>,
+    pub sequence: u64,
+    pub page: u64,
+    pub timestamp: i64,
+    pub work: KeeperWork,
+    pub keeper_custody: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
-pub struct KeeperWorkEntry {
-    pub timestamp: i64,
-    pub work_type: KeeperWorkType,
-    pub amount: u64,
-    #[max_len(100)]
-    pub details: String,
+#[event]
+pub struct AuthorityTransferProposed {
+    pub vault: Pubkey,
+    pub role: AuthorityRole,
+    pub current: Pubkey,
+    pub pending: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub vault: Pubkey,
+    pub role: AuthorityRole,
+    pub previous: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub vault: Pubkey,
+    pub role: AuthorityRole,
+    pub cancelled: Pubkey,
+}
+
+#[event]
+pub struct ConfigChangeQueued {
+    pub vault: Pubkey,
+    pub id: u64,
+    pub change: ConfigChange,
+    pub execute_after: i64,
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct PoolRegistry {
+#[event]
+pub struct ConfigChangeApproved {
     pub vault: Pubkey,
-    #[max_len(50)]
-    pub pools: Vec<Pubkey>,
+    pub id: u64,
+    pub signer: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
 }
 
-// Enums
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum ExclusionAction {
-    Add,
-    Remove,
+#[event]
+pub struct ConfigChangeExecuted {
+    pub vault: Pubkey,
+    pub id: u64,
+    pub change: ConfigChange,
+    pub executor: Pubkey,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
-pub enum KeeperWorkType {
-    HarvestFees,
-    SwapToRewardToken,
-    DistributeToOwner,
-    DistributeToHolders,
-    KeeperTopUp,
+#[event]
+pub struct ConfigChangeCancelled {
+    pub vault: Pubkey,
+    pub id: u64,
 }
 
 // Errors
@@ -750,4 +4416,871 @@ pub enum VaultError {
     
     #[msg("Launch time already set")]
     LaunchTimeAlreadySet,
-}
\ No newline at end of file
+    
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    
+    #[msg("Invalid owner token account")]
+    InvalidOwnerTokenAccount,
+    
+    #[msg("Invalid reward dial account")]
+    InvalidRewardDial,
+    
+    #[msg("Reward mint is not in the Smart Dial basket")]
+    RewardMintMismatch,
+    
+    #[msg("Invalid recipient count")]
+    InvalidRecipientCount,
+    
+    #[msg("Epoch escrow underfunded")]
+    EscrowUnderfunded,
+    
+    #[msg("Invalid claim index")]
+    InvalidClaimIndex,
+    
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    
+    #[msg("Reward already claimed")]
+    AlreadyClaimed,
+    
+    #[msg("Withheld fees below harvest threshold")]
+    BelowHarvestThreshold,
+    
+    #[msg("Invalid source token account")]
+    InvalidSourceAccount,
+    
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    
+    #[msg("Token mint does not match vault mint")]
+    InvalidTokenMint,
+    
+    #[msg("Vault token account must be the vault's associated token account")]
+    InvalidVaultTokenAccount,
+    
+    #[msg("Keeper token account does not hold the vault mint")]
+    InvalidKeeperTokenAccount,
+    
+    #[msg("Destination token account does not hold the vault mint")]
+    InvalidDestinationTokenAccount,
+    
+    #[msg("Keeper authority must differ from authority")]
+    KeeperMatchesAuthority,
+    
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+    
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    
+    #[msg("Invalid timelock delay")]
+    InvalidTimelock,
+    
+    #[msg("Required account missing")]
+    MissingAccount,
+    
+    #[msg("Action must go through a signer set proposal")]
+    MultisigRequired,
+    
+    #[msg("Signer set not enabled")]
+    MultisigNotEnabled,
+    
+    #[msg("Invalid signer set or threshold")]
+    InvalidSignerSet,
+    
+    #[msg("Already approved")]
+    AlreadyApproved,
+    
+    #[msg("Not enough approvals")]
+    InsufficientApprovals,
+    
+    #[msg("Withdrawal exceeds the per-window limit")]
+    WithdrawRateLimitExceeded,
+    
+    #[msg("Withdrawal exceeds unwithdrawn holder fees")]
+    WithdrawExceedsUnwithdrawnFees,
+    
+    #[msg("Invalid rate limit")]
+    InvalidRateLimit,
+    
+    #[msg("Fee ledger does not reconcile with token balances")]
+    LedgerMismatch,
+    
+    #[msg("Logged amount exceeds keeper custody")]
+    ExceedsKeeperCustody,
+    
+    #[msg("Pool registry pages must be created in order")]
+    InvalidRegistryPage,
+    
+    #[msg("Pool registry page full")]
+    PoolRegistryFull,
+    
+    #[msg("Pool already registered")]
+    PoolAlreadyRegistered,
+    
+    #[msg("Pool not registered")]
+    PoolNotRegistered,
+    
+    #[msg("Pools must be added through on-chain verification")]
+    PoolVerificationRequired,
+    
+    #[msg("Pool program is not an allowlisted DEX")]
+    DexNotAllowlisted,
+    
+    #[msg("DEX program already allowlisted")]
+    DexAlreadyAllowlisted,
+    
+    #[msg("DEX allowlist full")]
+    DexAllowlistFull,
+    
+    #[msg("Account is not a pool of the expected DEX")]
+    InvalidPoolAccount,
+    
+    #[msg("Pool does not trade the vault token mint")]
+    PoolMintMismatch,
+    
+    #[msg("Wallet is not excluded")]
+    NotExcluded,
+    
+    #[msg("Invalid exclusion marker account")]
+    InvalidExclusionMarker,
+    
+    #[msg("Unsupported account version")]
+    UnsupportedVersion,
+    
+    #[msg("Transfer fee exceeds the configured cap")]
+    TransferFeeAboveCap,
+    
+    #[msg("Invalid transfer fee cap")]
+    InvalidFeeCap,
+    
+    #[msg("Fee schedule steps must start at launch and be at least two epochs apart")]
+    InvalidFeeSchedule,
+    
+    #[msg("Fee schedule cannot change after launch")]
+    FeeScheduleLocked,
+    
+    #[msg("Launch time not set")]
+    LaunchTimeNotSet,
+    
+    #[msg("No fee schedule step is due")]
+    NoFeeStepDue,
+    
+    #[msg("Fee schedule step already active")]
+    FeeStepAlreadyActive,
+    
+    #[msg("Launch time too far from the current time")]
+    InvalidLaunchTime,
+    
+    #[msg("Launch requires a registered pool")]
+    LaunchPoolRequired,
+    
+    #[msg("Account passed more than once")]
+    DuplicateAccount,
+    
+    #[msg("Fee schedule step not yet in effect on the mint")]
+    FeeStepPending,
+    
+    #[msg("Transfer fee change still pending on the mint")]
+    FeeChangePending,
+    
+    #[msg("Every other pool registry page must be passed, in page order")]
+    RegistryPagesMismatch,
+    
+    #[msg("Launch fee step is not the mint's transfer fee")]
+    LaunchFeeNotOnMint,
+    
+    #[msg("Account must be migrated to the current layout first")]
+    AccountNotMigrated,
+    
+    #[msg("Keeper allowance exceeds the owner share")]
+    KeeperAllowanceTooHigh,
+    
+    #[msg("Keeper SOL balance does not need a top-up")]
+    KeeperTopUpNotNeeded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SPACING: i64 = 345_600; // Two mainnet epochs at the default slot time
+    
+    fn fee_vault() -> VaultState {
+        VaultState {
+            fee_bps_cap: 1_000,
+            max_fee_cap: u64::MAX,
+            ..Default::default()
+        }
+    }
+    
+    fn step(starts_after: i64, basis_points: u16) -> FeeStep {
+        FeeStep { starts_after, basis_points, maximum_fee: u64::MAX }
+    }
+    
+    fn withdraw_vault() -> VaultState {
+        VaultState {
+            withdraw_window: 100,
+            max_withdraw_per_window: 100,
+            max_withdraw_bps: BPS_DENOMINATOR as u16,
+            ledger: FeeLedger { owed_to_holders: 1_000_000, ..Default::default() },
+            ..Default::default()
+        }
+    }
+    
+    fn pool_data(name: &str, len: usize, fields: &[(usize, Pubkey)]) -> Vec<u8> {
+        let mut data = vec![0u8; len];
+        let discriminator = hashv(&[format!("account:{}", name).as_bytes()]).to_bytes();
+        data[..8].copy_from_slice(&discriminator[..8]);
+        for (offset, key) in fields {
+            data[*offset..*offset + 32].copy_from_slice(key.as_ref());
+        }
+        data
+    }
+    
+    #[test]
+    fn fee_schedule_accepts_steps_at_min_spacing() {
+        let steps = [step(0, 1_000), step(SPACING, 500), step(3 * SPACING, 100)];
+        assert!(validate_fee_schedule(&fee_vault(), &steps, SPACING).is_ok());
+    }
+    
+    #[test]
+    fn fee_schedule_rejects_bad_ordering() {
+        let vault = fee_vault();
+        let cases: [&[FeeStep]; 5] = [
+            &[],
+            &[step(SPACING, 500)],
+            &[step(0, 500), step(SPACING, 400), step(SPACING, 300)],
+            &[step(0, 500), step(2 * SPACING, 400), step(SPACING, 300)],
+            &[step(0, 500), step(SPACING - 1, 400)],
+        ];
+        for steps in cases {
+            assert_eq!(
+                validate_fee_schedule(&vault, steps, SPACING).unwrap_err(),
+                VaultError::InvalidFeeSchedule.into()
+            );
+        }
+    }
+    
+    #[test]
+    fn fee_schedule_requires_positive_gaps_without_spacing() {
+        let steps = [step(0, 500), step(0, 400)];
+        assert_eq!(
+            validate_fee_schedule(&fee_vault(), &steps, 0).unwrap_err(),
+            VaultError::InvalidFeeSchedule.into()
+        );
+        assert!(validate_fee_schedule(&fee_vault(), &[step(0, 500), step(1, 400)], 0).is_ok());
+    }
+    
+    #[test]
+    fn fee_schedule_checks_caps_and_state() {
+        let steps = [step(0, 1_001)];
+        assert_eq!(
+            validate_fee_schedule(&fee_vault(), &steps, SPACING).unwrap_err(),
+            VaultError::TransferFeeAboveCap.into()
+        );
+        
+        let launched = VaultState { launch_timestamp: 1, ..fee_vault() };
+        assert_eq!(
+            validate_fee_schedule(&launched, &[step(0, 500)], SPACING).unwrap_err(),
+            VaultError::FeeScheduleLocked.into()
+        );
+        
+        let pending = VaultState { pending_fee_step: Some(0), ..fee_vault() };
+        assert_eq!(
+            validate_fee_schedule(&pending, &[step(0, 500)], SPACING).unwrap_err(),
+            VaultError::FeeStepPending.into()
+        );
+    }
+    
+    #[test]
+    fn withdrawals_count_until_their_bucket_leaves_the_window() {
+        let mut vault = withdraw_vault();
+        
+        // Buckets are 50s, the window cap is shared across the newest three
+        vault.record_withdrawal(60, 1_000).unwrap();
+        vault.record_withdrawal(40, 1_049).unwrap();
+        assert_eq!(
+            vault.record_withdrawal(1, 1_049).unwrap_err(),
+            VaultError::WithdrawRateLimitExceeded.into()
+        );
+        assert_eq!(
+            vault.record_withdrawal(1, 1_050).unwrap_err(),
+            VaultError::WithdrawRateLimitExceeded.into()
+        );
+        assert_eq!(
+            vault.record_withdrawal(1, 1_149).unwrap_err(),
+            VaultError::WithdrawRateLimitExceeded.into()
+        );
+        assert_eq!(vault.window_withdrawn, 100);
+        
+        vault.record_withdrawal(100, 1_150).unwrap();
+        assert_eq!(vault.window_withdrawn, 100);
+        assert_eq!(vault.withdraw_buckets, [100, 0, 0]);
+    }
+    
+    #[test]
+    fn withdrawals_roll_out_bucket_by_bucket() {
+        let mut vault = withdraw_vault();
+        
+        vault.record_withdrawal(30, 1_000).unwrap();
+        vault.record_withdrawal(30, 1_060).unwrap();
+        vault.record_withdrawal(40, 1_110).unwrap();
+        assert_eq!(vault.withdraw_buckets, [40, 30, 30]);
+        
+        // Only the oldest bucket drops out
+        vault.record_withdrawal(30, 1_150).unwrap();
+        assert_eq!(vault.withdraw_buckets, [30, 40, 30]);
+        assert_eq!(vault.window_withdrawn, 100);
+        
+        // A gap longer than the window clears every bucket
+        vault.record_withdrawal(100, 10_000).unwrap();
+        assert_eq!(vault.withdraw_buckets, [100, 0, 0]);
+        assert_eq!(vault.window_start, 10_000);
+    }
+    
+    #[test]
+    fn withdrawals_limited_to_share_of_unwithdrawn_fees() {
+        let mut vault = VaultState {
+            max_withdraw_per_window: u64::MAX,
+            max_withdraw_bps: 5_000,
+            ledger: FeeLedger { owed_to_holders: 1_000, ..Default::default() },
+            ..withdraw_vault()
+        };
+        
+        assert_eq!(
+            vault.record_withdrawal(501, 1_000).unwrap_err(),
+            VaultError::WithdrawExceedsUnwithdrawnFees.into()
+        );
+        assert_eq!(vault.window_withdrawn, 0);
+        
+        vault.record_withdrawal(500, 1_000).unwrap();
+        assert_eq!(vault.ledger.owed_to_holders, 500);
+        assert_eq!(
+            vault.record_withdrawal(251, 1_000).unwrap_err(),
+            VaultError::WithdrawExceedsUnwithdrawnFees.into()
+        );
+    }
+    
+    #[test]
+    fn decodes_raydium_cpmm_pool() {
+        let (mint_0, vault_0, mint_1, vault_1) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = pool_data("PoolState", 637, &[(72, vault_0), (104, vault_1), (168, mint_0), (200, mint_1)]);
+        
+        assert_eq!(
+            DexKind::RaydiumCpmm.decode_pool(&data).unwrap(),
+            [(mint_0, vault_0), (mint_1, vault_1)]
+        );
+    }
+    
+    #[test]
+    fn decodes_orca_whirlpool() {
+        let (mint_a, vault_a, mint_b, vault_b) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let data = pool_data("Whirlpool", 653, &[(101, mint_a), (133, vault_a), (181, mint_b), (213, vault_b)]);
+        
+        assert_eq!(
+            DexKind::OrcaWhirlpool.decode_pool(&data).unwrap(),
+            [(mint_a, vault_a), (mint_b, vault_b)]
+        );
+    }
+    
+    #[test]
+    fn rejects_foreign_or_short_pool_data() {
+        let whirlpool = pool_data("Whirlpool", 653, &[]);
+        assert_eq!(
+            DexKind::RaydiumCpmm.decode_pool(&whirlpool).unwrap_err(),
+            VaultError::InvalidPoolAccount.into()
+        );
+        
+        let truncated = pool_data("PoolState", 231, &[]);
+        assert_eq!(
+            DexKind::RaydiumCpmm.decode_pool(&truncated).unwrap_err(),
+            VaultError::InvalidPoolAccount.into()
+        );
+        assert_eq!(
+            DexKind::RaydiumCpmm.decode_pool(&[]).unwrap_err(),
+            VaultError::InvalidPoolAccount.into()
+        );
+    }
+    
+    fn account_data(discriminator: &[u8], body: &impl AnchorSerialize, len: usize) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        body.serialize(&mut data).unwrap();
+        data.resize(len, 0);
+        data
+    }
+    
+    fn baseline_vault(authority: Pubkey) -> VaultStateV0 {
+        VaultStateV0 {
+            authority,
+            keeper_authority: Pubkey::new_unique(),
+            owner_wallet: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            min_hold_amount: 100_000,
+            reward_exclusions: vec![authority],
+            harvest_threshold: 500_000,
+            total_fees_harvested: 7_000,
+            total_rewards_distributed: 6_000,
+            pending_withheld: 40,
+            last_harvest_time: 1_000,
+            last_harvest_amount: 3_000,
+            last_distribution_time: 2_000,
+            launch_timestamp: 900,
+        }
+    }
+    
+    #[test]
+    fn migrates_baseline_vault_with_defaults() {
+        let authority = Pubkey::new_unique();
+        let legacy = baseline_vault(authority);
+        let data = account_data(VaultState::DISCRIMINATOR, &legacy, 8 + VaultStateV0::INIT_SPACE);
+        
+        let (vault, from_version) = VaultState::decode_any(&data).unwrap();
+        assert_eq!(from_version, 0);
+        assert_eq!(vault.version, VAULT_STATE_VERSION);
+        assert_eq!(vault.authority, authority);
+        assert_eq!(vault.token_mint, legacy.token_mint);
+        assert_eq!(vault.reward_exclusions, vec![authority]);
+        assert_eq!(vault.total_fees_harvested, 7_000);
+        assert_eq!(vault.launch_timestamp, 900);
+        assert_eq!((vault.owner_tax_share, vault.holders_tax_share), (OWNER_TAX_SHARE, HOLDERS_TAX_SHARE));
+        assert_eq!(vault.config_delay, DEFAULT_CONFIG_DELAY);
+        assert_eq!(vault.fee_bps_cap, DEFAULT_FEE_BPS_CAP);
+        assert_eq!(vault.admin_threshold, 0);
+        
+        // The migrated layout decodes as current from then on
+        let mut migrated = vec![0u8; 8 + VaultState::INIT_SPACE];
+        vault.try_serialize(&mut &mut migrated[..]).unwrap();
+        let (again, from_version) = VaultState::decode_any(&migrated).unwrap();
+        assert_eq!(from_version, VAULT_STATE_VERSION);
+        assert_eq!(again.authority, authority);
+    }
+    
+    #[test]
+    fn reads_version_ahead_of_stale_tail_bytes() {
+        let vault = VaultState {
+            version: VAULT_STATE_VERSION,
+            authority: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        let mut data = vec![0xff; 8 + VaultState::INIT_SPACE];
+        vault.try_serialize(&mut &mut data[..]).unwrap();
+        
+        let (decoded, from_version) = VaultState::decode_any(&data).unwrap();
+        assert_eq!(from_version, VAULT_STATE_VERSION);
+        assert_eq!(decoded.authority, vault.authority);
+        
+        // Only the byte after the discriminator decides, whatever the tail holds
+        data[8] = VAULT_STATE_VERSION + 1;
+        assert_eq!(
+            VaultState::decode_any(&data).map(|(_, version)| version).unwrap_err(),
+            VaultError::UnsupportedVersion.into()
+        );
+    }
+    
+    #[test]
+    fn rejects_other_accounts_as_vaults() {
+        let legacy = baseline_vault(Pubkey::new_unique());
+        let data = account_data(PoolRegistry::DISCRIMINATOR, &legacy, 8 + VaultStateV0::INIT_SPACE);
+        assert_eq!(
+            VaultState::decode_any(&data).map(|(_, version)| version).unwrap_err(),
+            ErrorCode::AccountDiscriminatorMismatch.into()
+        );
+        assert_eq!(
+            VaultState::decode_any(VaultState::DISCRIMINATOR).map(|(_, version)| version).unwrap_err(),
+            ErrorCode::AccountDiscriminatorMismatch.into()
+        );
+    }
+    
+    #[test]
+    fn migrates_legacy_registry_entries() {
+        let (vault, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registry = PoolRegistry::from_v0(PoolRegistryV0 { vault, pools: vec![pool] }, 1_234);
+        
+        assert_eq!((registry.version, registry.vault, registry.page), (POOL_REGISTRY_VERSION, vault, 0));
+        let entry = &registry.pools[0];
+        assert_eq!((entry.pool, entry.token_vault, entry.added_at), (pool, pool, 1_234));
+        assert_eq!(entry.dex_program, Pubkey::default());
+        
+        let mut data = vec![0u8; 8 + PoolRegistry::INIT_SPACE];
+        registry.try_serialize(&mut &mut data[..]).unwrap();
+        assert_eq!(data[8], POOL_REGISTRY_VERSION);
+    }
+    
+    #[test]
+    fn ledger_balances_must_cover_their_buckets() {
+        let vault = VaultState {
+            ledger: FeeLedger {
+                harvested: 300,
+                owed_to_owner: 200,
+                owed_to_holders: 400,
+                keeper_custody: 100,
+                paid_out: 1_000,
+            },
+            ..Default::default()
+        };
+        
+        // Untracked deposits above a bucket are fine
+        assert!(vault.reconcile_ledger(Some(500), Some(400), Some(100)).is_ok());
+        assert!(vault.reconcile_ledger(Some(600), Some(401), Some(150)).is_ok());
+        assert!(vault.reconcile_ledger(None, None, None).is_ok());
+        
+        let short = [
+            (Some(499), None, None),
+            (None, Some(399), None),
+            (None, None, Some(99)),
+        ];
+        for (vault_balance, treasury_balance, keeper_balance) in short {
+            assert_eq!(
+                vault.reconcile_ledger(vault_balance, treasury_balance, keeper_balance).unwrap_err(),
+                VaultError::LedgerMismatch.into()
+            );
+        }
+    }
+    
+    #[test]
+    fn ledger_buckets_never_wrap() {
+        let mut bucket = u64::MAX - 1;
+        ledger_credit(&mut bucket, 1).unwrap();
+        assert_eq!(ledger_credit(&mut bucket, 1).unwrap_err(), VaultError::MathOverflow.into());
+        assert_eq!(bucket, u64::MAX);
+        
+        let mut bucket = 1;
+        assert_eq!(ledger_debit(&mut bucket, 2).unwrap_err(), VaultError::MathOverflow.into());
+        assert_eq!(bucket, 1);
+    }
+    
+    #[test]
+    fn withdrawals_move_holder_share_out_of_ledger() {
+        let mut vault = withdraw_vault();
+        vault.record_withdrawal(60, 1_000).unwrap();
+        assert_eq!(vault.ledger.owed_to_holders, 999_940);
+        
+        // A bucket that would wrap is rejected rather than silently reset
+        vault.withdraw_buckets[0] = u64::MAX;
+        vault.window_withdrawn = 0;
+        assert_eq!(
+            vault.record_withdrawal(1, 1_000).unwrap_err(),
+            VaultError::MathOverflow.into()
+        );
+    }
+    
+    #[test]
+    fn only_spending_work_draws_on_keeper_custody() {
+        let spending = [
+            KeeperWork::DistributeToOwner { mint: Pubkey::default(), recipient: Pubkey::default(), input_amount: 7, amount: 5 },
+            KeeperWork::DistributeToHolders { reward_mint: Pubkey::default(), epoch: 0, recipient_count: 3, input_amount: 7, amount: 5 },
+            KeeperWork::KeeperTopUp { input_amount: 7, output_lamports: 5 },
+        ];
+        for work in spending {
+            assert_eq!(work.custody_spent(), 7);
+        }
+        
+        let swap = KeeperWork::SwapToRewardToken {
+            input_mint: Pubkey::default(),
+            output_mint: Pubkey::default(),
+            input_amount: 7,
+            output_amount: 5,
+            route_id: [0; 32],
+        };
+        assert_eq!(swap.custody_spent(), 0);
+    }
+    
+    fn claim_leaf(index: u32, amount: u64) -> [u8; 32] {
+        hashv(&[&[MERKLE_LEAF_PREFIX], &index.to_le_bytes(), Pubkey::new_unique().as_ref(), &amount.to_le_bytes()])
+            .to_bytes()
+    }
+    
+    fn merkle_node(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[MERKLE_NODE_PREFIX], &low, &high]).to_bytes()
+    }
+    
+    #[test]
+    fn merkle_proofs_verify_every_leaf() {
+        let leaves: Vec<[u8; 32]> = (0..4).map(|index| claim_leaf(index, 100)).collect();
+        let (left, right) = (merkle_node(leaves[0], leaves[1]), merkle_node(leaves[2], leaves[3]));
+        let root = merkle_node(left, right);
+        
+        assert!(verify_merkle_proof(&[leaves[1], right], root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], right], root, leaves[1]));
+        assert!(verify_merkle_proof(&[leaves[3], left], root, leaves[2]));
+        assert!(verify_merkle_proof(&[leaves[2], left], root, leaves[3]));
+        
+        // Single-leaf trees need no proof
+        assert!(verify_merkle_proof(&[], leaves[0], leaves[0]));
+    }
+    
+    #[test]
+    fn merkle_proofs_reject_wrong_leaves_and_paths() {
+        let leaves: Vec<[u8; 32]> = (0..4).map(|index| claim_leaf(index, 100)).collect();
+        let (left, right) = (merkle_node(leaves[0], leaves[1]), merkle_node(leaves[2], leaves[3]));
+        let root = merkle_node(left, right);
+        
+        assert!(!verify_merkle_proof(&[leaves[1], right], root, claim_leaf(0, 101)));
+        assert!(!verify_merkle_proof(&[leaves[1]], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[right, leaves[1]], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right, left], root, leaves[0]));
+        assert!(!verify_merkle_proof(&[leaves[1], right], [0; 32], leaves[0]));
+    }
+    
+    #[test]
+    fn claim_bitmap_tracks_each_index() {
+        assert_eq!(DistributionEpoch::bitmap_len(0), 0);
+        assert_eq!(DistributionEpoch::bitmap_len(1), 1);
+        assert_eq!(DistributionEpoch::bitmap_len(8), 1);
+        assert_eq!(DistributionEpoch::bitmap_len(9), 2);
+        assert_eq!(DistributionEpoch::bitmap_len(MAX_EPOCH_RECIPIENTS), 8_192);
+        
+        let recipient_count = 17;
+        let mut epoch = DistributionEpoch {
+            vault: Pubkey::default(),
+            epoch: 0,
+            reward_mint: Pubkey::default(),
+            merkle_root: [0; 32],
+            total_amount: 0,
+            claimed_amount: 0,
+            recipient_count,
+            created_at: 0,
+            claimed_bitmap: vec![0; DistributionEpoch::bitmap_len(recipient_count)],
+        };
+        for index in [0, 7, 8, 16] {
+            assert!(!epoch.is_claimed(index));
+            epoch.set_claimed(index);
+            assert!(epoch.is_claimed(index));
+        }
+        assert_eq!(epoch.claimed_bitmap, [0b1000_0001, 0b0000_0001, 0b0000_0001]);
+        assert!((0..recipient_count)
+            .filter(|index| ![0, 7, 8, 16].contains(index))
+            .all(|index| !epoch.is_claimed(index)));
+    }
+    
+    #[test]
+    fn dial_basket_read_only_from_smart_dial_accounts() {
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; DIAL_BASKET_OFFSET + 1 + DIAL_MAX_BASKET * DIAL_BASKET_ENTRY_LEN];
+        data[..8].copy_from_slice(&hashv(&[b"account:DialState"]).to_bytes()[..8]);
+        data[8] = DIAL_LAYOUT_VERSION;
+        data[DIAL_BASKET_OFFSET] = mints.len() as u8;
+        for (index, mint) in mints.iter().enumerate() {
+            let start = DIAL_BASKET_OFFSET + 1 + index * DIAL_BASKET_ENTRY_LEN;
+            data[start..start + 32].copy_from_slice(mint.as_ref());
+        }
+        
+        let key = Pubkey::new_unique();
+        let (mut lamports, mut dial_data) = (1, data.clone());
+        let dial = AccountInfo::new(&key, false, false, &mut lamports, &mut dial_data, &SMART_DIAL_ID, false, 0);
+        assert_eq!(read_dial_reward_basket(&dial).unwrap(), mints);
+        
+        // The same bytes under any other owner are not a dial
+        let forger = Pubkey::new_unique();
+        let (mut lamports, mut forged_data) = (1, data);
+        let forged = AccountInfo::new(&key, false, false, &mut lamports, &mut forged_data, &forger, false, 0);
+        assert_eq!(read_dial_reward_basket(&forged).unwrap_err(), VaultError::InvalidRewardDial.into());
+    }
+    
+    #[test]
+    fn timelock_covers_only_owner_hold_threshold_and_emergencies() {
+        let vault = VaultState { config_delay: 100, emergency_delay: 10, ..Default::default() };
+        let update = |update: ConfigUpdate| ConfigChange::UpdateConfig { update };
+        
+        for timelocked in [
+            ConfigUpdate { owner_wallet: Some(Pubkey::new_unique()), ..Default::default() },
+            ConfigUpdate { min_hold_amount: Some(1), ..Default::default() },
+            ConfigUpdate { harvest_threshold: Some(1), reward_dial: Some(Pubkey::new_unique()), ..Default::default() },
+        ] {
+            assert_eq!(update(timelocked).delay(&vault), 100);
+        }
+        
+        let emergencies = [
+            ConfigChange::EmergencyWithdraw { amount: 1, destination: Pubkey::default() },
+            ConfigChange::EmergencyWithdrawWithheld { destination: Pubkey::default() },
+            ConfigChange::EmergencyWithdrawTreasury { amount: 1, destination: Pubkey::default() },
+        ];
+        for change in emergencies {
+            assert_eq!(change.delay(&vault), 10);
+        }
+        
+        let immediate = [
+            update(ConfigUpdate { max_withdraw_bps: Some(1), config_delay: Some(0), ..Default::default() }),
+            ConfigChange::SetTransferFee { basis_points: 100, maximum_fee: 1 },
+            ConfigChange::SetFeeSchedule { steps: vec![step(0, 100)] },
+            ConfigChange::ProposeKeeperAuthority { keeper: Pubkey::new_unique() },
+        ];
+        for change in immediate {
+            assert_eq!(change.delay(&vault), 0);
+        }
+    }
+    
+    #[test]
+    fn migrations_need_admin_threshold_under_signer_set() {
+        let (authority, admins) = (Pubkey::new_unique(), [Pubkey::new_unique(), Pubkey::new_unique()]);
+        let outsider = Pubkey::new_unique();
+        let vault = VaultState {
+            authority,
+            admin_signers: admins.to_vec(),
+            admin_threshold: 2,
+            ..Default::default()
+        };
+        
+        let mut lamports = [0u64; 4];
+        let mut data: [[u8; 0]; 4] = [[]; 4];
+        let [l0, l1, l2, l3] = &mut lamports;
+        let [d0, d1, d2, d3] = &mut data;
+        let accounts = [
+            AccountInfo::new(&admins[0], true, false, l0, d0, &system_program::ID, false, 0),
+            AccountInfo::new(&admins[0], true, false, l1, d1, &system_program::ID, false, 0),
+            AccountInfo::new(&outsider, true, false, l2, d2, &system_program::ID, false, 0),
+            AccountInfo::new(&admins[1], false, false, l3, d3, &system_program::ID, false, 0),
+        ];
+        
+        // Repeated, outside and non-signing keys do not count
+        assert_eq!(
+            require_admin_threshold(&vault, &authority, &accounts).unwrap_err(),
+            VaultError::InsufficientApprovals.into()
+        );
+        assert!(require_admin_threshold(&vault, &admins[1], &accounts[..1]).is_ok());
+        
+        let single = VaultState { authority, ..Default::default() };
+        assert!(require_admin_threshold(&single, &authority, &[]).is_ok());
+    }
+    
+    #[test]
+    fn authority_can_be_proposed_through_the_queue() {
+        let (authority, keeper) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vault = VaultState { authority, keeper_authority: keeper, ..Default::default() };
+        let propose = |authority| ConfigChange::ProposeAuthority { authority };
+        
+        assert!(validate_config_change(&vault, &propose(Pubkey::new_unique())).is_ok());
+        for rejected in [Pubkey::default(), authority] {
+            assert_eq!(
+                validate_config_change(&vault, &propose(rejected)).unwrap_err(),
+                VaultError::InvalidAuthority.into()
+            );
+        }
+        assert_eq!(
+            validate_config_change(&vault, &propose(keeper)).unwrap_err(),
+            VaultError::KeeperMatchesAuthority.into()
+        );
+    }
+    
+    #[test]
+    fn keeper_allowance_only_from_owner_share_while_low_on_sol() {
+        let low = KEEPER_TOP_UP_THRESHOLD - 1;
+        
+        assert!(check_keeper_allowance(100, 0, u64::MAX).is_ok());
+        assert!(check_keeper_allowance(100, 100, low).is_ok());
+        assert_eq!(
+            check_keeper_allowance(100, 101, low).unwrap_err(),
+            VaultError::KeeperAllowanceTooHigh.into()
+        );
+        assert_eq!(
+            check_keeper_allowance(100, 1, KEEPER_TOP_UP_THRESHOLD).unwrap_err(),
+            VaultError::KeeperTopUpNotNeeded.into()
+        );
+    }
+    
+    // Marker bytes behind an 8-byte prefix, where the runtime keeps the length `resize` rewrites
+    fn marker_buffer(marker: &ExclusionMarker) -> Vec<u8> {
+        let mut buffer = vec![0u8; 8 + 8 + ExclusionMarker::INIT_SPACE];
+        marker.try_serialize(&mut &mut buffer[8..]).unwrap();
+        buffer
+    }
+    
+    #[test]
+    fn exclusion_markers_are_per_vault_and_wallet() {
+        let (vault, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (marker, bump) = exclusion_marker_address(&vault, &wallet);
+        
+        assert_eq!(
+            Pubkey::create_program_address(&[EXCLUSION_SEED, vault.as_ref(), wallet.as_ref(), &[bump]], &crate::ID),
+            Ok(marker)
+        );
+        assert_ne!(exclusion_marker_address(&vault, &Pubkey::new_unique()).0, marker);
+        assert_ne!(exclusion_marker_address(&Pubkey::new_unique(), &wallet).0, marker);
+    }
+    
+    #[test]
+    fn system_exclusions_cover_each_role_once() {
+        let (authority, keeper, vault_key) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let vault = VaultState {
+            authority,
+            keeper_authority: keeper,
+            owner_wallet: authority,
+            reward_exclusions: vec![keeper, Pubkey::new_unique()],
+            ..Default::default()
+        };
+        
+        let system = system_exclusions(&vault, vault_key);
+        assert_eq!(system, [authority, keeper, vault_key, crate::ID]);
+        
+        // Migrated legacy entries keep the System reason only for these wallets
+        assert!(system.contains(&vault.reward_exclusions[0]));
+        assert!(!system.contains(&vault.reward_exclusions[1]));
+    }
+    
+    #[test]
+    fn marker_lookup_needs_program_owned_data() {
+        let key = Pubkey::new_unique();
+        let marker = ExclusionMarker {
+            vault: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            reason: ExclusionReason::Manual,
+            excluded_at: 1,
+            bump: 255,
+        };
+        
+        let (mut lamports, mut data) = (1, marker_buffer(&marker));
+        let owned = AccountInfo::new(&key, false, true, &mut lamports, &mut data[8..], &crate::ID, false, 0);
+        assert!(marker_exists(&owned));
+        
+        let (mut lamports, mut data, other_program) = (1, marker_buffer(&marker), Pubkey::new_unique());
+        let foreign = AccountInfo::new(&key, false, true, &mut lamports, &mut data[8..], &other_program, false, 0);
+        assert!(!marker_exists(&foreign));
+        
+        let (mut lamports, mut data) = (1, []);
+        let empty = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert!(!marker_exists(&empty));
+    }
+    
+    #[test]
+    fn closing_marker_refunds_rent_and_returns_its_reason() {
+        let (vault, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (key, bump) = exclusion_marker_address(&vault, &wallet);
+        let marker = ExclusionMarker { vault, wallet, reason: ExclusionReason::Pool, excluded_at: 1, bump };
+        
+        let (mut marker_lamports, mut marker_data, marker_owner) = (1_500, marker_buffer(&marker), crate::ID);
+        let (payer, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut payer_lamports, mut payer_data) = (1, []);
+        let (mut recipient_lamports, mut recipient_data) = (500, []);
+        let (mut system_lamports, mut system_data, loader) = (1, [], Pubkey::default());
+        let accounts = ExclusionMarkerAccounts {
+            marker: AccountInfo::new(
+                &key, false, true, &mut marker_lamports, &mut marker_data[8..], &marker_owner, false, 0,
+            ),
+            bump,
+            payer: AccountInfo::new(
+                &payer, true, true, &mut payer_lamports, &mut payer_data, &system_program::ID, false, 0,
+            ),
+            rent_recipient: AccountInfo::new(
+                &recipient, true, true, &mut recipient_lamports, &mut recipient_data, &system_program::ID, false, 0,
+            ),
+            system_program: AccountInfo::new(
+                &system_program::ID, false, false, &mut system_lamports, &mut system_data, &loader, true, 0,
+            ),
+        };
+        
+        assert_eq!(close_exclusion_marker(&accounts).unwrap(), ExclusionReason::Pool);
+        assert_eq!(accounts.rent_recipient.lamports(), 2_000);
+        assert_eq!(accounts.marker.lamports(), 0);
+        assert_eq!(accounts.marker.owner, &system_program::ID);
+        assert!(accounts.marker.data_is_empty());
+        assert!(!marker_exists(&accounts.marker));
+    }
+}
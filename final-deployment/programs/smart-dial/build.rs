@@ -25,7 +25,7 @@ fn main() {
             dest_path,
             format!(
                 r#"declare_id!("{}");"#,
-                keypair.pubkey().to_string()
+                keypair.pubkey()
             ),
         ).expect("Failed to write program ID");
     } else {
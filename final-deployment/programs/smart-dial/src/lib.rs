@@ -1,18 +1,48 @@
+// Anchor 0.31 emits a call to the deprecated AccountInfo::realloc in its IDL resize
+// instruction at the crate root, where no narrower allow reaches. Drop with Anchor 0.32.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{token, token_2022};
+use absolute_vault::{VaultState, VAULT_STATE_VERSION};
+use spl_token_2022::{
+    extension::{
+        permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig,
+        transfer_hook::TransferHook,
+        BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::Mint as MintState,
+};
 
 // Program ID is dynamically generated from keypair at compile time
 include!(concat!(env!("OUT_DIR"), "/program_id.rs"));
 
 pub const DIAL_STATE_SEED: &[u8] = b"dial_state";
 pub const SECONDS_PER_WEEK: i64 = 7 * 24 * 60 * 60;
-pub const UPDATE_COOLDOWN: i64 = 24 * 60 * 60; // 24 hours
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+pub const FIRST_MONDAY_OFFSET: i64 = 4 * SECONDS_PER_DAY; // 1970-01-05, the first Monday after the Unix epoch
+pub const MAX_HISTORY: usize = 52; // A year of weekly updates, as the singleton dial kept
+pub const MAX_SCHEDULED: usize = 4;
+pub const MAX_BASKET: usize = 3;
+pub const BASKET_WEIGHT_TOTAL: u16 = 10_000;
+// Fixed offsets for consumers reading the basket without the full layout
+pub const REWARD_BASKET_OFFSET: usize = 73; // Right after `current_reward_token`, holds the basket length
+pub const BASKET_ENTRY_LEN: usize = 67; // mint, weight_bps, decimals, token_program
+pub const ALLOWLIST_SEED: &[u8] = b"allowlist";
+pub const DENYLIST_SEED: &[u8] = b"denylist";
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const DIAL_STATE_VERSION: u8 = 1;
 pub const SOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
 #[program]
 pub mod smart_dial {
     use super::*;
 
-    /// Initialize Smart Dial with launch timestamp
+    /// Initialize the dial for an absolute-vault with launch timestamp (vault authority only)
+    /// Each vault, and so each governed mint, gets its own dial at `[DIAL_STATE_SEED, vault]`
     pub fn initialize(
         ctx: Context<Initialize>,
         launch_timestamp: i64,
@@ -21,109 +51,540 @@ pub mod smart_dial {
         
         dial.authority = ctx.accounts.authority.key();
         dial.current_reward_token = SOL_MINT; // SOL is default reward token
+        dial.reward_basket = RewardBasket::single(SOL_MINT, 9, token::ID);
+        dial.current_reward_decimals = 9;
+        dial.current_reward_token_program = token::ID;
+        dial.risk_policy = RiskPolicy::default();
+        dial.scheduled = Vec::new();
         dial.last_update = 0;
         dial.update_count = 0;
         dial.launch_timestamp = launch_timestamp;
+        dial.pending_authority = Pubkey::default();
+        dial.vault = ctx.accounts.vault.key();
+        dial.version = DIAL_STATE_VERSION;
         
         // Initialize update history
         dial.update_history = Vec::new();
         
         msg!("Smart Dial initialized");
         msg!("Authority: {}", dial.authority);
+        msg!("Vault: {} (mint {})", dial.vault, ctx.accounts.vault.token_mint);
         msg!("Initial reward token: SOL");
         msg!("Launch timestamp: {}", launch_timestamp);
         
+        emit!(DialInitialized {
+            dial_state: dial.key(),
+            vault: dial.vault,
+            token_mint: ctx.accounts.vault.token_mint,
+            authority: dial.authority,
+            reward_token: dial.current_reward_token,
+            launch_timestamp,
+        });
+        
         Ok(())
     }
 
-    /// Update reward token for the week
-    pub fn update_reward_token(
-        ctx: Context<UpdateRewardToken>,
-        new_reward_token: Pubkey,
+    /// Update reward token for the current week (once per Monday-to-Monday epoch)
+    /// The new mint must be an SPL Token or Token-2022 mint that passes the risk policy
+    pub fn update_reward_token(ctx: Context<UpdateRewardToken>) -> Result<()> {
+        let basket = single_mint_basket(ctx.accounts)?;
+        let authority = ctx.accounts.authority.key();
+        apply_basket(&mut ctx.accounts.dial_state, authority, basket)
+    }
+
+    /// Update the weighted reward basket for the current week
+    /// Remaining accounts are `[mint, allowlist_entry, denylist_entry]` per weight, in order
+    pub fn update_reward_basket(ctx: Context<UpdateAuthority>, weights: Vec<u16>) -> Result<()> {
+        let basket = basket_from_accounts(&ctx.accounts.dial_state, ctx.remaining_accounts, &weights)?;
+        let authority = ctx.accounts.authority.key();
+        apply_basket(&mut ctx.accounts.dial_state, authority, basket)
+    }
+
+    /// Schedule the reward token for a future epoch, it activates at that epoch's Monday
+    pub fn schedule_reward_token(ctx: Context<UpdateRewardToken>, epoch: u64) -> Result<()> {
+        let basket = single_mint_basket(ctx.accounts)?;
+        schedule_basket(&mut ctx.accounts.dial_state, basket, epoch)
+    }
+
+    /// Schedule a weighted reward basket for a future epoch, accounts as in `update_reward_basket`
+    pub fn schedule_reward_basket(
+        ctx: Context<UpdateAuthority>,
+        epoch: u64,
+        weights: Vec<u16>,
     ) -> Result<()> {
+        let basket = basket_from_accounts(&ctx.accounts.dial_state, ctx.remaining_accounts, &weights)?;
+        schedule_basket(&mut ctx.accounts.dial_state, basket, epoch)
+    }
+
+    /// Drop the basket scheduled for an epoch that has not started
+    pub fn cancel_scheduled_token(ctx: Context<UpdateAuthority>, epoch: u64) -> Result<()> {
         let dial = &mut ctx.accounts.dial_state;
-        let current_time = Clock::get()?.unix_timestamp;
+        let dial_key = dial.key();
+        dial.activate_due(dial_key, Clock::get()?.unix_timestamp)?;
         
-        // Calculate first Monday after launch
-        let first_monday = calculate_first_monday(dial.launch_timestamp);
+        let index = dial.scheduled
+            .iter()
+            .position(|scheduled| scheduled.epoch == epoch)
+            .ok_or(DialError::NothingScheduled)?;
+        let cancelled = dial.scheduled.remove(index);
         
-        // Check if we're past the first Monday
-        require!(
-            current_time >= first_monday,
-            DialError::TooEarlyToUpdate
-        );
+        emit!(ScheduledTokenCancelled {
+            dial_state: dial_key,
+            epoch,
+            basket: cancelled.basket.weights(),
+        });
         
-        // Enforce cooldown (except for first update)
-        if dial.last_update > 0 {
-            require!(
-                current_time >= dial.last_update + UPDATE_COOLDOWN,
-                DialError::UpdateCooldown
-            );
-        }
+        msg!("Scheduled basket for epoch {} cancelled", epoch);
         
-        // Store in update history
-        if dial.update_history.len() >= 52 { // Keep last year of history
-            dial.update_history.remove(0);
-        }
+        Ok(())
+    }
+
+    /// Activate scheduled baskets whose epoch has started (permissionless)
+    pub fn activate_scheduled_token(ctx: Context<ActivateScheduledToken>) -> Result<()> {
+        let dial = &mut ctx.accounts.dial_state;
+        let dial_key = dial.key();
+        let activated = dial.activate_due(dial_key, Clock::get()?.unix_timestamp)?;
+        require!(activated > 0, DialError::NothingScheduled);
+        
+        Ok(())
+    }
+
+    /// Reward basket in effect at `timestamp`, resolved from history and schedule (read-only)
+    pub fn get_reward_basket_at(ctx: Context<ReadDialState>, timestamp: i64) -> Result<Vec<BasketEntry>> {
+        ctx.accounts.dial_state.reward_basket_at(timestamp)
+    }
+
+    /// Add a mint to the allowlist or denylist under the `$SYMBOL` it was picked by
+    /// A mint sits on one list at most, remove it from the other list first.
+    pub fn add_token_list_entry(
+        ctx: Context<AddTokenListEntry>,
+        list: TokenList,
+        mint: Pubkey,
+        symbol: String,
+    ) -> Result<()> {
+        require!(!list_entry_exists(&ctx.accounts.other_entry), DialError::MintOnOtherList);
+        let symbol = normalize_symbol(&symbol)?;
+        let clock = Clock::get()?;
         
-        // Store values before mutable borrow
-        let old_token = dial.current_reward_token;
-        let update_number = dial.update_count;
+        let entry = &mut ctx.accounts.entry;
+        entry.dial_state = ctx.accounts.dial_state.key();
+        entry.mint = mint;
+        entry.symbol = symbol.clone();
+        entry.added_at = clock.unix_timestamp;
+        entry.bump = ctx.bumps.entry;
         
-        dial.update_history.push(UpdateRecord {
-            timestamp: current_time,
-            old_token,
-            new_token: new_reward_token,
-            update_number,
+        emit!(TokenListUpdated {
+            dial_state: entry.dial_state,
+            list,
+            mint,
+            symbol: symbol.clone(),
+            added: true,
+            timestamp: clock.unix_timestamp,
         });
         
-        // Update reward token
-        dial.current_reward_token = new_reward_token;
-        dial.last_update = current_time;
-        dial.update_count += 1;
+        msg!("${} ({}) added to {:?}", symbol, mint, list);
         
-        msg!("Reward token updated to: {}", new_reward_token);
-        msg!("Update count: {}", dial.update_count);
-        msg!("Next update available after: {}", current_time + UPDATE_COOLDOWN);
+        Ok(())
+    }
+
+    /// Remove a mint from the allowlist or denylist
+    pub fn remove_token_list_entry(
+        ctx: Context<RemoveTokenListEntry>,
+        list: TokenList,
+        mint: Pubkey,
+    ) -> Result<()> {
+        let entry = &ctx.accounts.entry;
+        
+        emit!(TokenListUpdated {
+            dial_state: entry.dial_state,
+            list,
+            mint,
+            symbol: entry.symbol.clone(),
+            added: false,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("${} ({}) removed from {:?}", entry.symbol, mint, list);
+        
+        Ok(())
+    }
+
+    /// Set which mint risks `update_reward_token` tolerates
+    pub fn set_risk_policy(ctx: Context<UpdateAuthority>, policy: RiskPolicy) -> Result<()> {
+        let dial = &mut ctx.accounts.dial_state;
+        dial.risk_policy = policy;
+        
+        emit!(RiskPolicyUpdated {
+            dial_state: dial.key(),
+            policy,
+        });
+        
+        msg!("Risk policy updated: {:?}", policy);
         
         Ok(())
     }
 
-    /// Transfer authority
-    pub fn update_authority(
+    /// Copy the launch time from the linked vault (permissionless)
+    pub fn sync_launch_time(ctx: Context<SyncLaunchTime>) -> Result<()> {
+        let launch_timestamp = ctx.accounts.vault.launch_timestamp;
+        require!(launch_timestamp > 0, DialError::LaunchTimeNotSet);
+        
+        let dial = &mut ctx.accounts.dial_state;
+        let previous = std::mem::replace(&mut dial.launch_timestamp, launch_timestamp);
+        
+        emit!(LaunchTimeSynced {
+            dial_state: dial.key(),
+            vault: ctx.accounts.vault.key(),
+            previous,
+            launch_timestamp,
+        });
+        
+        msg!("Launch timestamp synced from vault: {}", launch_timestamp);
+        
+        Ok(())
+    }
+
+    /// Propose a new authority, takes effect once it calls `accept_authority`
+    pub fn transfer_authority(
         ctx: Context<UpdateAuthority>,
         new_authority: Pubkey,
     ) -> Result<()> {
-        ctx.accounts.dial_state.authority = new_authority;
+        require_keys_neq!(new_authority, Pubkey::default(), DialError::InvalidAuthority);
+        
+        let dial = &mut ctx.accounts.dial_state;
+        require_keys_neq!(new_authority, dial.authority, DialError::InvalidAuthority);
+        dial.pending_authority = new_authority;
+        
+        emit!(AuthorityTransferProposed {
+            dial_state: dial.key(),
+            current: dial.authority,
+            pending: new_authority,
+        });
+        
+        msg!("Authority transfer proposed to: {}", new_authority);
+        
+        Ok(())
+    }
+
+    /// Accept a pending authority transfer (proposed key only)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let dial = &mut ctx.accounts.dial_state;
+        let new_authority = ctx.accounts.new_authority.key();
+        
+        require_keys_eq!(dial.pending_authority, new_authority, DialError::Unauthorized);
+        
+        let previous = std::mem::replace(&mut dial.authority, new_authority);
+        dial.pending_authority = Pubkey::default();
+        
+        emit!(AuthorityTransferAccepted {
+            dial_state: dial.key(),
+            previous,
+            new_authority,
+        });
         
         msg!("Authority updated to: {}", new_authority);
         
         Ok(())
     }
+
+    /// Cancel a pending authority transfer
+    pub fn cancel_authority_transfer(ctx: Context<UpdateAuthority>) -> Result<()> {
+        let dial = &mut ctx.accounts.dial_state;
+        
+        let cancelled = dial.pending_authority;
+        require_keys_neq!(cancelled, Pubkey::default(), DialError::NoPendingAuthority);
+        dial.pending_authority = Pubkey::default();
+        
+        emit!(AuthorityTransferCancelled {
+            dial_state: dial.key(),
+            cancelled,
+        });
+        
+        msg!("Authority transfer to {} cancelled", cancelled);
+        
+        Ok(())
+    }
+
+    /// Move the legacy singleton dial to its vault's dial account and close it
+    /// Needs the dial authority and the vault authority, the singleton may predate versioning.
+    /// Repoint the vault's `reward_dial` to the new account alongside.
+    pub fn migrate_singleton_dial(ctx: Context<MigrateSingletonDial>) -> Result<()> {
+        let info = ctx.accounts.legacy_dial_state.to_account_info();
+        let vault_key = ctx.accounts.vault.key();
+        
+        let (mut dial, from_version) = DialState::decode_any(&info.try_borrow_data()?)?;
+        require_keys_eq!(dial.authority, ctx.accounts.authority.key(), DialError::Unauthorized);
+        
+        // A singleton already linked elsewhere belongs to that vault
+        require!(
+            dial.vault == Pubkey::default() || dial.vault == vault_key,
+            DialError::InvalidVault
+        );
+        dial.vault = vault_key;
+        ctx.accounts.dial_state.set_inner(dial);
+        
+        // Close the singleton, rent goes to the dial authority
+        let lamports = info.lamports();
+        let authority = ctx.accounts.authority.to_account_info();
+        **authority.try_borrow_mut_lamports()? = authority
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(DialError::MathOverflow)?;
+        **info.try_borrow_mut_lamports()? = 0;
+        info.assign(&system_program::ID);
+        info.resize(0)?;
+        
+        emit!(DialMigrated {
+            dial_state: ctx.accounts.dial_state.key(),
+            legacy_dial_state: info.key(),
+            vault: vault_key,
+            from_version,
+            to_version: DIAL_STATE_VERSION,
+        });
+        
+        msg!("Singleton dial moved to {} for vault {}", ctx.accounts.dial_state.key(), vault_key);
+        
+        Ok(())
+    }
 }
 
-// Helper function to calculate first Monday after launch
-fn calculate_first_monday(launch_timestamp: i64) -> i64 {
-    // Calculate days since Unix epoch for launch
-    let launch_days = launch_timestamp / (24 * 60 * 60);
-    
-    // January 1, 1970 was a Thursday (day 4, where Monday = 1)
-    // So days_since_epoch % 7 gives us: 0=Thu, 1=Fri, 2=Sat, 3=Sun, 4=Mon, 5=Tue, 6=Wed
-    let launch_day_of_week = ((launch_days + 4) % 7) as i64;
-    
-    // Calculate days until next Monday
-    // If launch is Monday (4), days_until = 7 (next Monday)
-    // If launch is Tuesday (5), days_until = 6
-    // If launch is Sunday (3), days_until = 1
-    let days_until_monday = if launch_day_of_week == 4 {
-        7 // If launch is Monday, wait until next Monday
-    } else if launch_day_of_week < 4 {
-        4 - launch_day_of_week // Thu=0->4, Fri=1->3, Sat=2->2, Sun=3->1
-    } else {
-        11 - launch_day_of_week // Tue=5->6, Wed=6->5
+// Check the reward mint is a real mint acceptable under the risk policy, returns its decimals
+fn validate_reward_mint(mint: &AccountInfo, policy: &RiskPolicy, epoch: u64) -> Result<u8> {
+    require!(
+        *mint.owner == token::ID || *mint.owner == token_2022::ID,
+        DialError::InvalidRewardMint
+    );
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)
+        .map_err(|_| error!(DialError::InvalidRewardMint))?;
+    
+    require!(
+        policy.allow_freeze_authority || mint.base.freeze_authority.is_none(),
+        DialError::FreezeAuthorityNotAllowed
+    );
+    if let Ok(extension) = mint.get_extension::<PermanentDelegate>() {
+        require!(
+            policy.allow_permanent_delegate || Option::<Pubkey>::from(extension.delegate).is_none(),
+            DialError::PermanentDelegateNotAllowed
+        );
+    }
+    if let Ok(extension) = mint.get_extension::<TransferHook>() {
+        require!(
+            policy.allow_transfer_hook || Option::<Pubkey>::from(extension.program_id).is_none(),
+            DialError::TransferHookNotAllowed
+        );
+    }
+    if let Ok(extension) = mint.get_extension::<TransferFeeConfig>() {
+        // A scheduled fee counts as well as the one in effect
+        let active = u16::from(extension.get_epoch_fee(epoch).transfer_fee_basis_points);
+        let newer = u16::from(extension.newer_transfer_fee.transfer_fee_basis_points);
+        require!(
+            policy.allow_transfer_fee || (active == 0 && newer == 0),
+            DialError::TransferFeeNotAllowed
+        );
+    }
+    
+    Ok(mint.base.decimals)
+}
+
+// Put `basket` in effect for the current epoch, at most once per week
+fn apply_basket(dial: &mut Account<DialState>, authority: Pubkey, basket: RewardBasket) -> Result<()> {
+    let dial_key = dial.key();
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // Baskets scheduled for epochs that have started take their slot first
+    dial.activate_due(dial_key, current_time)?;
+    
+    // Check if we're past the first Monday
+    let epoch = epoch_index(dial.launch_timestamp, current_time)
+        .ok_or(DialError::TooEarlyToUpdate)?;
+    
+    // One update per week
+    require!(
+        dial.last_update == 0 || epoch_index(dial.launch_timestamp, dial.last_update) != Some(epoch),
+        DialError::UpdateCooldown
+    );
+    
+    let (old_token, update_number) = dial.record_activation(basket, epoch, current_time);
+    let next_update_after = epoch_start(dial.launch_timestamp, epoch + 1);
+    let primary = basket.primary();
+    
+    msg!("Reward token updated to: {}", primary.mint);
+    msg!("Basket: {:?}", basket.weights());
+    msg!("Update count: {}", dial.update_count);
+    msg!("Next update available after: {}", next_update_after);
+    
+    emit!(RewardTokenUpdated {
+        dial_state: dial_key,
+        authority,
+        old_token,
+        new_token: primary.mint,
+        decimals: primary.decimals,
+        token_program: primary.token_program,
+        basket: basket.weights(),
+        update_number,
+        update_count: dial.update_count,
+        timestamp: current_time,
+        next_update_after,
+    });
+    
+    Ok(())
+}
+
+// Queue `basket` for a future epoch, replacing whatever that epoch had
+fn schedule_basket(dial: &mut Account<DialState>, basket: RewardBasket, epoch: u64) -> Result<()> {
+    let dial_key = dial.key();
+    let current_time = Clock::get()?.unix_timestamp;
+    dial.activate_due(dial_key, current_time)?;
+    
+    if let Some(current) = epoch_index(dial.launch_timestamp, current_time) {
+        require!(epoch > current, DialError::EpochNotInFuture);
+    }
+    
+    let entry = ScheduledBasket {
+        epoch,
+        basket,
+        scheduled_at: current_time,
     };
+    match dial.scheduled.binary_search_by_key(&epoch, |scheduled| scheduled.epoch) {
+        Ok(index) => dial.scheduled[index] = entry,
+        Err(index) => {
+            require!(dial.scheduled.len() < MAX_SCHEDULED, DialError::ScheduleFull);
+            dial.scheduled.insert(index, entry);
+        }
+    }
+    let activates_at = epoch_start(dial.launch_timestamp, epoch);
+    
+    emit!(RewardTokenScheduled {
+        dial_state: dial_key,
+        epoch,
+        basket: basket.weights(),
+        activates_at,
+    });
+    
+    msg!("Reward basket {:?} scheduled for epoch {} ({})", basket.weights(), epoch, activates_at);
+    
+    Ok(())
+}
+
+// The `UpdateRewardToken` mint as a basket of one
+fn single_mint_basket(accounts: &UpdateRewardToken) -> Result<RewardBasket> {
+    let entry = basket_entry(
+        &accounts.dial_state,
+        &accounts.reward_mint,
+        &accounts.allowlist_entry,
+        &accounts.denylist_entry,
+        BASKET_WEIGHT_TOTAL,
+    )?;
+    RewardBasket::new(&[entry])
+}
+
+// Build a basket from `[mint, allowlist_entry, denylist_entry]` account triples
+fn basket_from_accounts(
+    dial: &Account<DialState>,
+    accounts: &[AccountInfo],
+    weights: &[u16],
+) -> Result<RewardBasket> {
+    require!(accounts.len() == weights.len() * 3, DialError::InvalidBasket);
     
-    launch_timestamp + (days_until_monday * 24 * 60 * 60)
+    let entries = accounts
+        .chunks_exact(3)
+        .zip(weights)
+        .map(|(chunk, &weight_bps)| {
+            let [mint, allowlist_entry, denylist_entry] = chunk else {
+                unreachable!()
+            };
+            require_keys_eq!(
+                allowlist_entry.key(),
+                token_list_address(TokenList::Allow, &dial.key(), mint.key),
+                DialError::InvalidListEntry
+            );
+            require_keys_eq!(
+                denylist_entry.key(),
+                token_list_address(TokenList::Deny, &dial.key(), mint.key),
+                DialError::InvalidListEntry
+            );
+            basket_entry(dial, mint, allowlist_entry, denylist_entry, weight_bps)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    
+    RewardBasket::new(&entries)
+}
+
+// Validate one basket mint against the risk policy and token lists
+fn basket_entry(
+    dial: &DialState,
+    mint: &AccountInfo,
+    allowlist_entry: &AccountInfo,
+    denylist_entry: &AccountInfo,
+    weight_bps: u16,
+) -> Result<BasketEntry> {
+    let decimals = validate_reward_mint(mint, &dial.risk_policy, Clock::get()?.epoch)?;
+    check_token_lists(&dial.risk_policy, mint.key, allowlist_entry, denylist_entry)?;
+    
+    Ok(BasketEntry {
+        mint: mint.key(),
+        weight_bps,
+        decimals,
+        token_program: *mint.owner,
+    })
+}
+
+// Reject denylisted mints, and mints missing from the allowlist in strict mode
+fn check_token_lists(
+    policy: &RiskPolicy,
+    mint: &Pubkey,
+    allowlist_entry: &AccountInfo,
+    denylist_entry: &AccountInfo,
+) -> Result<()> {
+    require!(!list_entry_exists(denylist_entry), DialError::MintDenied);
+    
+    if list_entry_exists(allowlist_entry) {
+        let data = allowlist_entry.try_borrow_data()?;
+        let entry = TokenListEntry::try_deserialize(&mut &data[..])?;
+        msg!("Reward token {} allowlisted as ${}", mint, entry.symbol);
+    } else {
+        require!(!policy.require_allowlist, DialError::MintNotAllowlisted);
+    }
+    
+    Ok(())
+}
+
+fn token_list_address(list: TokenList, dial_key: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[list.seed(), dial_key.as_ref(), mint.as_ref()], &crate::ID).0
+}
+
+// Entry PDAs are only ever created by this program, so an owned account means listed
+fn list_entry_exists(entry: &AccountInfo) -> bool {
+    *entry.owner == crate::ID && !entry.data_is_empty()
+}
+
+// Accept `$SYMBOL` or `SYMBOL`, stored without the `$`
+fn normalize_symbol(symbol: &str) -> Result<String> {
+    let symbol = symbol.strip_prefix('$').unwrap_or(symbol);
+    require!(
+        !symbol.is_empty()
+            && symbol.len() <= MAX_SYMBOL_LEN
+            && symbol.chars().all(|c| c.is_ascii_alphanumeric()),
+        DialError::InvalidSymbol
+    );
+    Ok(symbol.to_string())
+}
+
+// First Monday 00:00 UTC strictly after launch, epoch 0 starts there
+fn calculate_first_monday(launch_timestamp: i64) -> i64 {
+    let weeks = (launch_timestamp - FIRST_MONDAY_OFFSET).div_euclid(SECONDS_PER_WEEK) + 1;
+    FIRST_MONDAY_OFFSET + weeks * SECONDS_PER_WEEK
+}
+
+// Weekly epoch containing `timestamp`, none before the first Monday
+fn epoch_index(launch_timestamp: i64, timestamp: i64) -> Option<u64> {
+    let first_monday = calculate_first_monday(launch_timestamp);
+    (timestamp >= first_monday).then(|| ((timestamp - first_monday) / SECONDS_PER_WEEK) as u64)
+}
+
+fn epoch_start(launch_timestamp: i64, epoch: u64) -> i64 {
+    calculate_first_monday(launch_timestamp) + epoch as i64 * SECONDS_PER_WEEK
 }
 
 // Account structures
@@ -134,11 +595,15 @@ pub struct Initialize<'info> {
         init,
         payer = payer,
         space = 8 + DialState::INIT_SPACE,
-        seeds = [DIAL_STATE_SEED],
+        seeds = [DIAL_STATE_SEED, vault.key().as_ref()],
         bump
     )]
     pub dial_state: Account<'info, DialState>,
     
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ DialError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+    
+    #[account(address = vault.authority @ DialError::Unauthorized)]
     pub authority: Signer<'info>,
     
     #[account(mut)]
@@ -151,12 +616,86 @@ pub struct Initialize<'info> {
 pub struct UpdateRewardToken<'info> {
     #[account(
         mut,
-        seeds = [DIAL_STATE_SEED],
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated,
+        constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
+    )]
+    pub dial_state: Account<'info, DialState>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: New reward mint, owner and extensions checked by the handler
+    pub reward_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Allowlist PDA for the mint, may not exist
+    #[account(
+        seeds = [ALLOWLIST_SEED, dial_state.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: UncheckedAccount<'info>,
+    
+    /// CHECK: Denylist PDA for the mint, may not exist
+    #[account(
+        seeds = [DENYLIST_SEED, dial_state.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub denylist_entry: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(list: TokenList, mint: Pubkey)]
+pub struct AddTokenListEntry<'info> {
+    #[account(
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated,
+        constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
+    )]
+    pub dial_state: Account<'info, DialState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TokenListEntry::INIT_SPACE,
+        seeds = [list.seed(), dial_state.key().as_ref(), mint.as_ref()],
+        bump
+    )]
+    pub entry: Account<'info, TokenListEntry>,
+    
+    /// CHECK: The mint's entry PDA on the other list, may not exist
+    #[account(
+        seeds = [list.other().seed(), dial_state.key().as_ref(), mint.as_ref()],
+        bump
+    )]
+    pub other_entry: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(list: TokenList, mint: Pubkey)]
+pub struct RemoveTokenListEntry<'info> {
+    #[account(
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
         bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated,
         constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
     )]
     pub dial_state: Account<'info, DialState>,
     
+    #[account(
+        mut,
+        close = authority,
+        seeds = [list.seed(), dial_state.key().as_ref(), mint.as_ref()],
+        bump = entry.bump
+    )]
+    pub entry: Account<'info, TokenListEntry>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
 }
 
@@ -164,8 +703,9 @@ pub struct UpdateRewardToken<'info> {
 pub struct UpdateAuthority<'info> {
     #[account(
         mut,
-        seeds = [DIAL_STATE_SEED],
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
         bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated,
         constraint = dial_state.authority == authority.key() @ DialError::Unauthorized
     )]
     pub dial_state: Account<'info, DialState>,
@@ -173,26 +713,594 @@ pub struct UpdateAuthority<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated
+    )]
+    pub dial_state: Account<'info, DialState>,
+    
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateScheduledToken<'info> {
+    #[account(
+        mut,
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated
+    )]
+    pub dial_state: Account<'info, DialState>,
+}
+
+#[derive(Accounts)]
+pub struct ReadDialState<'info> {
+    #[account(
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated
+    )]
+    pub dial_state: Account<'info, DialState>,
+}
+
+#[derive(Accounts)]
+pub struct SyncLaunchTime<'info> {
+    #[account(
+        mut,
+        seeds = [DIAL_STATE_SEED, dial_state.vault.as_ref()],
+        bump,
+        has_one = vault @ DialError::InvalidVault,
+        constraint = dial_state.version == DIAL_STATE_VERSION @ DialError::AccountNotMigrated
+    )]
+    pub dial_state: Account<'info, DialState>,
+    
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ DialError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSingletonDial<'info> {
+    /// CHECK: Legacy singleton dial in any layout version, checked by the handler
+    #[account(
+        mut,
+        seeds = [DIAL_STATE_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub legacy_dial_state: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DialState::INIT_SPACE,
+        seeds = [DIAL_STATE_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub dial_state: Account<'info, DialState>,
+    
+    #[account(constraint = vault.version == VAULT_STATE_VERSION @ DialError::AccountNotMigrated)]
+    pub vault: Account<'info, VaultState>,
+    
+    /// Dial authority, checked against the singleton by the handler
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(address = vault.authority @ DialError::Unauthorized)]
+    pub vault_authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
 // State
 
 #[account]
 #[derive(InitSpace)]
 pub struct DialState {
+    pub version: u8, // Right after the discriminator, DIAL_STATE_VERSION once migrated
     pub authority: Pubkey,
-    pub current_reward_token: Pubkey,
+    pub current_reward_token: Pubkey, // Heaviest basket mint
+    pub reward_basket: RewardBasket, // Fixed size at REWARD_BASKET_OFFSET
     pub last_update: i64,
     pub update_count: u64,
     pub launch_timestamp: i64,
-    #[max_len(52)] // Keep last year of updates
+    #[max_len(MAX_HISTORY)]
     pub update_history: Vec<UpdateRecord>,
+    pub pending_authority: Pubkey,
+    pub vault: Pubkey, // absolute-vault this dial serves, part of the seeds
+    pub current_reward_decimals: u8,
+    pub current_reward_token_program: Pubkey,
+    pub risk_policy: RiskPolicy,
+    #[max_len(MAX_SCHEDULED)]
+    pub scheduled: Vec<ScheduledBasket>, // Future epochs, ordered by epoch
+}
+
+// Creating the dial through CPI caps its size
+const _: () = assert!(8 + DialState::INIT_SPACE <= 10_240);
+// The legacy singleton layout is told apart by size alone
+const _: () = assert!(DialState::INIT_SPACE != DialStateV0::INIT_SPACE);
+const _: () = assert!(BasketEntry::INIT_SPACE == BASKET_ENTRY_LEN);
+// absolute-vault reads the basket through its own copy of the layout
+const _: () = assert!(
+    DIAL_STATE_VERSION == absolute_vault::DIAL_LAYOUT_VERSION
+        && REWARD_BASKET_OFFSET == absolute_vault::DIAL_BASKET_OFFSET
+        && BASKET_ENTRY_LEN == absolute_vault::DIAL_BASKET_ENTRY_LEN
+        && MAX_BASKET == absolute_vault::DIAL_MAX_BASKET
+);
+
+impl DialState {
+    // Decode a dial in the current layout or the legacy singleton one
+    // V0 predates the version byte and is recognised by its fixed size, any other layout
+    // is picked by the version byte stored right after the discriminator
+    fn decode_any(data: &[u8]) -> Result<(Self, u8)> {
+        require!(
+            data.len() > 8 && &data[..8] == DialState::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        
+        if data.len() == 8 + DialStateV0::INIT_SPACE {
+            let legacy = DialStateV0::deserialize(&mut &data[8..])?;
+            return Ok((legacy.upgrade(), 0));
+        }
+        require!(data[8] == DIAL_STATE_VERSION, DialError::UnsupportedVersion);
+        Ok((DialState::try_deserialize(&mut &data[..])?, DIAL_STATE_VERSION))
+    }
+    
+    // Make `basket` current and record the change, returns the old primary token and update number
+    fn record_activation(&mut self, basket: RewardBasket, epoch: u64, activated_at: i64) -> (Pubkey, u64) {
+        if self.update_history.len() >= MAX_HISTORY {
+            self.update_history.remove(0);
+        }
+        
+        let old_token = self.current_reward_token;
+        let update_number = self.update_count;
+        
+        self.update_history.push(UpdateRecord {
+            timestamp: activated_at,
+            update_number,
+            epoch,
+            basket: basket.entries().iter().map(HistoryEntry::from).collect(),
+        });
+        
+        let primary = basket.primary();
+        self.current_reward_token = primary.mint;
+        self.current_reward_decimals = primary.decimals;
+        self.current_reward_token_program = primary.token_program;
+        self.reward_basket = basket;
+        self.last_update = activated_at;
+        self.update_count += 1;
+        
+        (old_token, update_number)
+    }
+    
+    // Move scheduled baskets whose epoch has started into effect, as of their Monday
+    fn activate_due(&mut self, dial_key: Pubkey, now: i64) -> Result<usize> {
+        let Some(current) = epoch_index(self.launch_timestamp, now) else {
+            return Ok(0);
+        };
+        
+        let mut activated = 0;
+        while self.scheduled.first().is_some_and(|scheduled| scheduled.epoch <= current) {
+            let scheduled = self.scheduled.remove(0);
+            let activated_at = epoch_start(self.launch_timestamp, scheduled.epoch);
+            let (old_token, update_number) = self.record_activation(
+                scheduled.basket,
+                scheduled.epoch,
+                activated_at,
+            );
+            
+            emit!(RewardTokenActivated {
+                dial_state: dial_key,
+                epoch: scheduled.epoch,
+                old_token,
+                new_token: self.current_reward_token,
+                basket: scheduled.basket.weights(),
+                update_number,
+                activated_at,
+            });
+            
+            msg!("Scheduled reward basket active from epoch {}", scheduled.epoch);
+            activated += 1;
+        }
+        
+        Ok(activated)
+    }
+    
+    // Latest activation at or before `timestamp`, including schedule entries not yet activated
+    pub fn reward_basket_at(&self, timestamp: i64) -> Result<Vec<BasketEntry>> {
+        if let Some(scheduled) = self.scheduled
+            .iter()
+            .rev()
+            .find(|scheduled| epoch_start(self.launch_timestamp, scheduled.epoch) <= timestamp)
+        {
+            return Ok(scheduled.basket.entries().to_vec());
+        }
+        if let Some(record) = self.update_history
+            .iter()
+            .rev()
+            .find(|record| record.timestamp <= timestamp)
+        {
+            return Ok(record.basket.iter().map(HistoryEntry::to_entry).collect());
+        }
+        
+        // Before every recorded change the dial paid in SOL, only known if no history was dropped
+        require!(
+            self.update_history.len() as u64 == self.update_count,
+            DialError::HistoryUnavailable
+        );
+        Ok(RewardBasket::single(SOL_MINT, 9, token::ID).entries().to_vec())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct ScheduledBasket {
+    pub epoch: u64,
+    pub basket: RewardBasket,
+    pub scheduled_at: i64,
+}
+
+// Up to MAX_BASKET weighted mints, unused slots zeroed so the size never changes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct RewardBasket {
+    pub len: u8,
+    pub entries: [BasketEntry; MAX_BASKET],
+}
+
+impl RewardBasket {
+    // 1 to MAX_BASKET distinct mints, weights all non-zero and summing to BASKET_WEIGHT_TOTAL
+    pub fn new(entries: &[BasketEntry]) -> Result<Self> {
+        require!(
+            !entries.is_empty() && entries.len() <= MAX_BASKET,
+            DialError::InvalidBasket
+        );
+        let mut total: u32 = 0;
+        for (index, entry) in entries.iter().enumerate() {
+            require!(entry.weight_bps > 0, DialError::InvalidBasket);
+            require!(
+                entries[..index].iter().all(|other| other.mint != entry.mint),
+                DialError::InvalidBasket
+            );
+            total += entry.weight_bps as u32;
+        }
+        require!(total == BASKET_WEIGHT_TOTAL as u32, DialError::InvalidBasket);
+        
+        let mut basket = RewardBasket {
+            len: entries.len() as u8,
+            ..Default::default()
+        };
+        basket.entries[..entries.len()].copy_from_slice(entries);
+        Ok(basket)
+    }
+    
+    pub fn single(mint: Pubkey, decimals: u8, token_program: Pubkey) -> Self {
+        let mut basket = RewardBasket {
+            len: 1,
+            ..Default::default()
+        };
+        basket.entries[0] = BasketEntry {
+            mint,
+            weight_bps: BASKET_WEIGHT_TOTAL,
+            decimals,
+            token_program,
+        };
+        basket
+    }
+    
+    pub fn entries(&self) -> &[BasketEntry] {
+        &self.entries[..self.len as usize]
+    }
+    
+    // Heaviest entry, the first one on ties
+    pub fn primary(&self) -> BasketEntry {
+        self.entries()
+            .iter()
+            .fold(self.entries[0], |best, entry| {
+                if entry.weight_bps > best.weight_bps { *entry } else { best }
+            })
+    }
+    
+    pub fn weights(&self) -> Vec<BasketWeight> {
+        self.entries()
+            .iter()
+            .map(|entry| BasketWeight {
+                mint: entry.mint,
+                weight_bps: entry.weight_bps,
+            })
+            .collect()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct BasketEntry {
+    pub mint: Pubkey,
+    pub weight_bps: u16,
+    pub decimals: u8,
+    pub token_program: Pubkey,
+}
+
+// Basket entry as reported in events
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct BasketWeight {
+    pub mint: Pubkey,
+    pub weight_bps: u16,
+}
+
+// Mint features a reward token may have, all rejected by default
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct RiskPolicy {
+    pub allow_freeze_authority: bool,
+    pub allow_permanent_delegate: bool,
+    pub allow_transfer_hook: bool,
+    pub allow_transfer_fee: bool,
+    pub require_allowlist: bool, // Strict mode, only allowlisted mints
+}
+
+// One allowlist or denylist entry, keyed by dial and mint
+#[account]
+#[derive(InitSpace)]
+pub struct TokenListEntry {
+    pub dial_state: Pubkey,
+    pub mint: Pubkey,
+    #[max_len(MAX_SYMBOL_LEN)]
+    pub symbol: String, // Tweeted `$SYMBOL` without the `$`
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenList {
+    Allow,
+    Deny,
+}
+
+impl TokenList {
+    pub fn seed(&self) -> &'static [u8] {
+        match self {
+            TokenList::Allow => ALLOWLIST_SEED,
+            TokenList::Deny => DENYLIST_SEED,
+        }
+    }
+    
+    pub fn other(&self) -> TokenList {
+        match self {
+            TokenList::Allow => TokenList::Deny,
+            TokenList::Deny => TokenList::Allow,
+        }
+    }
+}
+
+// Singleton dial layout before versioning
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct DialStateV0 {
+    pub authority: Pubkey,
+    pub current_reward_token: Pubkey,
+    pub last_update: i64,
+    pub update_count: u64,
+    pub launch_timestamp: i64,
+    #[max_len(52)]
+    pub update_history: Vec<UpdateRecordV0>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct UpdateRecordV0 {
+    pub timestamp: i64,
+    pub old_token: Pubkey,
+    pub new_token: Pubkey,
+    pub update_number: u64,
+}
+
+impl DialStateV0 {
+    // The singleton never stored mint details, they are recorded on the next update
+    fn upgrade(self) -> DialState {
+        let launch_timestamp = self.launch_timestamp;
+        DialState {
+            authority: self.authority,
+            current_reward_token: self.current_reward_token,
+            reward_basket: RewardBasket::single(self.current_reward_token, 0, Pubkey::default()),
+            last_update: self.last_update,
+            update_count: self.update_count,
+            launch_timestamp,
+            update_history: self.update_history
+                .into_iter()
+                .map(|record| UpdateRecord {
+                    timestamp: record.timestamp,
+                    update_number: record.update_number,
+                    epoch: epoch_index(launch_timestamp, record.timestamp).unwrap_or(0),
+                    basket: vec![HistoryEntry {
+                        mint: record.new_token,
+                        weight_bps: BASKET_WEIGHT_TOTAL,
+                        decimals: 0,
+                        token_program: RewardTokenProgram::Unknown,
+                    }],
+                })
+                .collect(),
+            pending_authority: Pubkey::default(),
+            vault: Pubkey::default(),
+            current_reward_decimals: 0,
+            current_reward_token_program: Pubkey::default(),
+            risk_policy: RiskPolicy::default(),
+            scheduled: Vec::new(),
+            version: DIAL_STATE_VERSION,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct UpdateRecord {
     pub timestamp: i64,
+    pub update_number: u64,
+    pub epoch: u64,
+    #[max_len(MAX_BASKET)]
+    pub basket: Vec<HistoryEntry>, // Mint details as activated
+}
+
+// Basket entry as kept in history, the token program packed into one byte so MAX_HISTORY
+// records fit the CPI allocation limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct HistoryEntry {
+    pub mint: Pubkey,
+    pub weight_bps: u16,
+    pub decimals: u8,
+    pub token_program: RewardTokenProgram,
+}
+
+impl From<&BasketEntry> for HistoryEntry {
+    fn from(entry: &BasketEntry) -> Self {
+        HistoryEntry {
+            mint: entry.mint,
+            weight_bps: entry.weight_bps,
+            decimals: entry.decimals,
+            token_program: RewardTokenProgram::from_id(&entry.token_program),
+        }
+    }
+}
+
+impl HistoryEntry {
+    pub fn to_entry(&self) -> BasketEntry {
+        BasketEntry {
+            mint: self.mint,
+            weight_bps: self.weight_bps,
+            decimals: self.decimals,
+            token_program: self.token_program.id(),
+        }
+    }
+}
+
+// Unknown only for records carried over from the singleton dial
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum RewardTokenProgram {
+    Unknown,
+    Token,
+    Token2022,
+}
+
+impl RewardTokenProgram {
+    fn from_id(program: &Pubkey) -> Self {
+        if *program == token::ID {
+            RewardTokenProgram::Token
+        } else if *program == token_2022::ID {
+            RewardTokenProgram::Token2022
+        } else {
+            RewardTokenProgram::Unknown
+        }
+    }
+    
+    pub fn id(&self) -> Pubkey {
+        match self {
+            RewardTokenProgram::Unknown => Pubkey::default(),
+            RewardTokenProgram::Token => token::ID,
+            RewardTokenProgram::Token2022 => token_2022::ID,
+        }
+    }
+}
+
+// Events
+
+#[event]
+pub struct DialInitialized {
+    pub dial_state: Pubkey,
+    pub vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub reward_token: Pubkey,
+    pub launch_timestamp: i64,
+}
+
+#[event]
+pub struct LaunchTimeSynced {
+    pub dial_state: Pubkey,
+    pub vault: Pubkey,
+    pub previous: i64,
+    pub launch_timestamp: i64,
+}
+
+#[event]
+pub struct DialMigrated {
+    pub dial_state: Pubkey,
+    pub legacy_dial_state: Pubkey,
+    pub vault: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct RewardTokenUpdated {
+    pub dial_state: Pubkey,
+    pub authority: Pubkey,
     pub old_token: Pubkey,
     pub new_token: Pubkey,
+    pub decimals: u8,
+    pub token_program: Pubkey,
+    pub basket: Vec<BasketWeight>,
     pub update_number: u64,
+    pub update_count: u64,
+    pub timestamp: i64,
+    pub next_update_after: i64,
+}
+
+#[event]
+pub struct RewardTokenScheduled {
+    pub dial_state: Pubkey,
+    pub epoch: u64,
+    pub basket: Vec<BasketWeight>,
+    pub activates_at: i64,
+}
+
+#[event]
+pub struct ScheduledTokenCancelled {
+    pub dial_state: Pubkey,
+    pub epoch: u64,
+    pub basket: Vec<BasketWeight>,
+}
+
+#[event]
+pub struct RewardTokenActivated {
+    pub dial_state: Pubkey,
+    pub epoch: u64,
+    pub old_token: Pubkey,
+    pub new_token: Pubkey,
+    pub basket: Vec<BasketWeight>,
+    pub update_number: u64,
+    pub activated_at: i64,
+}
+
+#[event]
+pub struct TokenListUpdated {
+    pub dial_state: Pubkey,
+    pub list: TokenList,
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub added: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RiskPolicyUpdated {
+    pub dial_state: Pubkey,
+    pub policy: RiskPolicy,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub dial_state: Pubkey,
+    pub current: Pubkey,
+    pub pending: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferAccepted {
+    pub dial_state: Pubkey,
+    pub previous: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub dial_state: Pubkey,
+    pub cancelled: Pubkey,
 }
 
 // Errors
@@ -202,9 +1310,399 @@ pub enum DialError {
     #[msg("Unauthorized")]
     Unauthorized,
     
-    #[msg("Update cooldown not met (24 hours)")]
+    #[msg("Reward token already set for this week")]
     UpdateCooldown,
     
     #[msg("Cannot update before first Monday after launch")]
     TooEarlyToUpdate,
-}
\ No newline at end of file
+    
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+    
+    #[msg("Unsupported account version")]
+    UnsupportedVersion,
+    
+    #[msg("Vault is not linked to this dial")]
+    InvalidVault,
+    
+    #[msg("Vault launch time not set")]
+    LaunchTimeNotSet,
+    
+    #[msg("Reward token is not an SPL Token or Token-2022 mint")]
+    InvalidRewardMint,
+    
+    #[msg("Reward mint has a freeze authority")]
+    FreezeAuthorityNotAllowed,
+    
+    #[msg("Reward mint has a permanent delegate")]
+    PermanentDelegateNotAllowed,
+    
+    #[msg("Reward mint has a transfer hook")]
+    TransferHookNotAllowed,
+    
+    #[msg("Reward mint charges a transfer fee")]
+    TransferFeeNotAllowed,
+    
+    #[msg("Epoch has already started")]
+    EpochNotInFuture,
+    
+    #[msg("Reward token schedule full")]
+    ScheduleFull,
+    
+    #[msg("No reward token scheduled")]
+    NothingScheduled,
+    
+    #[msg("Timestamp is older than the kept update history")]
+    HistoryUnavailable,
+    
+    #[msg("Reward mint is denylisted")]
+    MintDenied,
+    
+    #[msg("Reward mint is not allowlisted")]
+    MintNotAllowlisted,
+    
+    #[msg("Symbol must be 1-10 alphanumeric characters")]
+    InvalidSymbol,
+    
+    #[msg("Basket needs 1-3 distinct mints with weights summing to 10,000 bps")]
+    InvalidBasket,
+    
+    #[msg("Account is not the token list entry for the mint")]
+    InvalidListEntry,
+    
+    #[msg("Math overflow")]
+    MathOverflow,
+    
+    #[msg("Account must be migrated to the current layout first")]
+    AccountNotMigrated,
+    
+    #[msg("Mint is already on the other token list")]
+    MintOnOtherList,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const MONDAY: i64 = 1_704_067_200; // 2024-01-01 00:00 UTC
+    
+    #[test]
+    fn first_monday_is_strictly_after_launch() {
+        assert_eq!(calculate_first_monday(MONDAY), MONDAY + SECONDS_PER_WEEK);
+        assert_eq!(calculate_first_monday(MONDAY - 1), MONDAY);
+        assert_eq!(calculate_first_monday(MONDAY - SECONDS_PER_DAY), MONDAY);
+        assert_eq!(calculate_first_monday(MONDAY - SECONDS_PER_WEEK), MONDAY);
+        assert_eq!(calculate_first_monday(MONDAY + SECONDS_PER_WEEK - 1), MONDAY + SECONDS_PER_WEEK);
+    }
+    
+    #[test]
+    fn first_monday_falls_on_monday_midnight() {
+        for launch in [0, 1, MONDAY - 1, MONDAY + 3 * SECONDS_PER_DAY + 12_345] {
+            let first_monday = calculate_first_monday(launch);
+            assert_eq!((first_monday - FIRST_MONDAY_OFFSET).rem_euclid(SECONDS_PER_WEEK), 0);
+            assert!(first_monday > launch && first_monday - launch <= SECONDS_PER_WEEK);
+        }
+    }
+    
+    #[test]
+    fn epoch_index_rolls_over_at_monday_midnight() {
+        let launch = MONDAY - SECONDS_PER_DAY;
+        
+        assert_eq!(epoch_index(launch, launch), None);
+        assert_eq!(epoch_index(launch, MONDAY - 1), None);
+        assert_eq!(epoch_index(launch, MONDAY), Some(0));
+        assert_eq!(epoch_index(launch, MONDAY + SECONDS_PER_WEEK - 1), Some(0));
+        assert_eq!(epoch_index(launch, MONDAY + SECONDS_PER_WEEK), Some(1));
+        assert_eq!(epoch_index(launch, MONDAY + 10 * SECONDS_PER_WEEK + 1), Some(10));
+    }
+    
+    #[test]
+    fn epoch_start_matches_epoch_index() {
+        let launch = MONDAY + 2 * SECONDS_PER_DAY;
+        for epoch in 0..5 {
+            let start = epoch_start(launch, epoch);
+            assert_eq!(epoch_index(launch, start), Some(epoch));
+            assert_eq!(epoch_index(launch, start - 1), epoch.checked_sub(1));
+        }
+    }
+    
+    fn legacy_dial(launch_timestamp: i64, records: u64) -> DialStateV0 {
+        DialStateV0 {
+            authority: Pubkey::new_unique(),
+            current_reward_token: SOL_MINT,
+            last_update: launch_timestamp + 2 * SECONDS_PER_WEEK,
+            update_count: records,
+            launch_timestamp,
+            update_history: (0..records)
+                .map(|update_number| UpdateRecordV0 {
+                    timestamp: MONDAY + update_number as i64 * SECONDS_PER_WEEK,
+                    old_token: SOL_MINT,
+                    new_token: Pubkey::new_unique(),
+                    update_number,
+                })
+                .collect(),
+        }
+    }
+    
+    fn legacy_data(legacy: &DialStateV0) -> Vec<u8> {
+        let mut data = DialState::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(8 + DialStateV0::INIT_SPACE, 0);
+        data
+    }
+    
+    #[test]
+    fn migrates_legacy_singleton_dial() {
+        let legacy = legacy_dial(MONDAY - SECONDS_PER_DAY, 2);
+        let (dial, from_version) = DialState::decode_any(&legacy_data(&legacy)).unwrap();
+        
+        assert_eq!(from_version, 0);
+        assert_eq!(dial.version, DIAL_STATE_VERSION);
+        assert_eq!(dial.authority, legacy.authority);
+        assert_eq!(dial.vault, Pubkey::default());
+        assert_eq!(dial.reward_basket.entries()[0].mint, SOL_MINT);
+        assert_eq!(dial.update_history.len(), 2);
+        
+        let record = &dial.update_history[1];
+        assert_eq!((record.update_number, record.epoch), (1, 1));
+        assert_eq!(record.basket[0].mint, legacy.update_history[1].new_token);
+        assert_eq!(record.basket[0].weight_bps, BASKET_WEIGHT_TOTAL);
+    }
+    
+    #[test]
+    fn decodes_current_dial_by_leading_version() {
+        let (dial, _) = DialState::decode_any(&legacy_data(&legacy_dial(MONDAY, 1))).unwrap();
+        let mut data = vec![0xff; 8 + DialState::INIT_SPACE];
+        dial.try_serialize(&mut &mut data[..]).unwrap();
+        
+        let (decoded, from_version) = DialState::decode_any(&data).unwrap();
+        assert_eq!(from_version, DIAL_STATE_VERSION);
+        assert_eq!(decoded.authority, dial.authority);
+        
+        data[8] = DIAL_STATE_VERSION + 1;
+        assert_eq!(
+            DialState::decode_any(&data).map(|(_, version)| version).unwrap_err(),
+            DialError::UnsupportedVersion.into()
+        );
+    }
+    
+    #[test]
+    fn keeps_full_legacy_history() {
+        let legacy = legacy_dial(MONDAY - SECONDS_PER_DAY, 52);
+        let (dial, _) = DialState::decode_any(&legacy_data(&legacy)).unwrap();
+        assert_eq!(dial.update_history.len(), 52);
+        assert_eq!(dial.update_history[0].update_number, 0);
+    }
+    
+    #[test]
+    fn history_keeps_newest_records_with_mint_details() {
+        let (mut dial, _) = DialState::decode_any(&legacy_data(&legacy_dial(MONDAY, 52))).unwrap();
+        let mint = Pubkey::new_unique();
+        let basket = RewardBasket::single(mint, 6, token_2022::ID);
+        dial.record_activation(basket, 52, MONDAY + 52 * SECONDS_PER_WEEK);
+        
+        assert_eq!(dial.update_history.len(), MAX_HISTORY);
+        assert_eq!(dial.update_history[0].update_number, 1);
+        let entry = dial.update_history[MAX_HISTORY - 1].basket[0].to_entry();
+        assert_eq!((entry.mint, entry.decimals, entry.token_program), (mint, 6, token_2022::ID));
+        assert_eq!(
+            dial.reward_basket_at(MONDAY + 52 * SECONDS_PER_WEEK).unwrap()[0].token_program,
+            token_2022::ID
+        );
+        assert_eq!(dial.update_history[0].basket[0].token_program.id(), Pubkey::default());
+    }
+    
+    fn entry(weight_bps: u16) -> BasketEntry {
+        BasketEntry {
+            mint: Pubkey::new_unique(),
+            weight_bps,
+            decimals: 9,
+            token_program: token::ID,
+        }
+    }
+    
+    #[test]
+    fn basket_weights_must_sum_to_total() {
+        let basket = RewardBasket::new(&[entry(6_000), entry(3_000), entry(1_000)]).unwrap();
+        assert_eq!(basket.entries().len(), 3);
+        assert_eq!(basket.primary().weight_bps, 6_000);
+        
+        for weights in [&[9_999][..], &[10_001], &[5_000, 4_000], &[10_000, 0]] {
+            let entries: Vec<_> = weights.iter().map(|&weight| entry(weight)).collect();
+            assert_eq!(
+                RewardBasket::new(&entries).map(|basket| basket.len).unwrap_err(),
+                DialError::InvalidBasket.into()
+            );
+        }
+    }
+    
+    #[test]
+    fn basket_rejects_duplicates_and_size_limits() {
+        let first = entry(5_000);
+        let duplicate = BasketEntry { weight_bps: 5_000, ..first };
+        let too_many: Vec<_> = (0..=MAX_BASKET).map(|_| entry(2_500)).collect();
+        
+        for entries in [&[first, duplicate][..], &[], &too_many] {
+            assert_eq!(
+                RewardBasket::new(entries).map(|basket| basket.len).unwrap_err(),
+                DialError::InvalidBasket.into()
+            );
+        }
+    }
+    
+    #[test]
+    fn basket_sits_at_fixed_offset_in_dial_state() {
+        let entries = [entry(7_000), entry(3_000)];
+        let dial = DialState {
+            version: DIAL_STATE_VERSION,
+            authority: Pubkey::new_unique(),
+            current_reward_token: entries[0].mint,
+            reward_basket: RewardBasket::new(&entries).unwrap(),
+            last_update: 0,
+            update_count: 0,
+            launch_timestamp: 0,
+            update_history: Vec::new(),
+            pending_authority: Pubkey::default(),
+            vault: Pubkey::new_unique(),
+            current_reward_decimals: 9,
+            current_reward_token_program: token::ID,
+            risk_policy: RiskPolicy::default(),
+            scheduled: Vec::new(),
+        };
+        let mut data = vec![0u8; 8 + DialState::INIT_SPACE];
+        dial.try_serialize(&mut &mut data[..]).unwrap();
+        
+        assert_eq!(data[8], DIAL_STATE_VERSION);
+        assert_eq!(data[REWARD_BASKET_OFFSET], 2);
+        for (index, expected) in entries.iter().enumerate() {
+            let start = REWARD_BASKET_OFFSET + 1 + index * BASKET_ENTRY_LEN;
+            let bytes = &data[start..start + BASKET_ENTRY_LEN];
+            assert_eq!(&bytes[..32], expected.mint.as_ref());
+            assert_eq!(u16::from_le_bytes([bytes[32], bytes[33]]), expected.weight_bps);
+            assert_eq!(bytes[34], expected.decimals);
+            assert_eq!(&bytes[35..], expected.token_program.as_ref());
+        }
+        // Unused slots stay zeroed
+        let unused = REWARD_BASKET_OFFSET + 1 + 2 * BASKET_ENTRY_LEN;
+        assert!(data[unused..unused + BASKET_ENTRY_LEN].iter().all(|&byte| byte == 0));
+    }
+    
+    #[test]
+    fn basket_accounts_must_be_triples_of_list_entries() {
+        let dial_key = Pubkey::new_unique();
+        let (dial, _) = DialState::decode_any(&legacy_data(&legacy_dial(MONDAY, 0))).unwrap();
+        let mut dial_data = vec![0u8; 8 + DialState::INIT_SPACE];
+        dial.try_serialize(&mut &mut dial_data[..]).unwrap();
+        let mut dial_lamports = 1;
+        let dial_info = AccountInfo::new(
+            &dial_key, false, false, &mut dial_lamports, &mut dial_data, &crate::ID, false, 0,
+        );
+        let dial = Account::<DialState>::try_from(&dial_info).unwrap();
+        
+        let mint = Pubkey::new_unique();
+        let allowlist = token_list_address(TokenList::Allow, &dial_key, &mint);
+        let wrong = Pubkey::new_unique();
+        let (mut mint_lamports, mut allowlist_lamports, mut wrong_lamports) = (0, 0, 0);
+        let (mut mint_data, mut allowlist_data, mut wrong_data) = ([], [], []);
+        let accounts = [
+            AccountInfo::new(&mint, false, false, &mut mint_lamports, &mut mint_data, &token::ID, false, 0),
+            AccountInfo::new(
+                &allowlist, false, false, &mut allowlist_lamports, &mut allowlist_data, &system_program::ID, false, 0,
+            ),
+            AccountInfo::new(&wrong, false, false, &mut wrong_lamports, &mut wrong_data, &system_program::ID, false, 0),
+        ];
+        
+        assert_eq!(
+            basket_from_accounts(&dial, &accounts[..2], &[BASKET_WEIGHT_TOTAL])
+                .map(|basket| basket.len)
+                .unwrap_err(),
+            DialError::InvalidBasket.into()
+        );
+        assert_eq!(
+            basket_from_accounts(&dial, &accounts, &[BASKET_WEIGHT_TOTAL])
+                .map(|basket| basket.len)
+                .unwrap_err(),
+            DialError::InvalidListEntry.into()
+        );
+    }
+    
+    #[test]
+    fn vault_reads_dials_owned_by_this_program() {
+        assert_eq!(absolute_vault::SMART_DIAL_ID, crate::ID);
+    }
+    
+    fn list_entry_data(dial_key: Pubkey, mint: Pubkey) -> Vec<u8> {
+        let entry = TokenListEntry {
+            dial_state: dial_key,
+            mint,
+            symbol: "BONK".to_string(),
+            added_at: MONDAY,
+            bump: 255,
+        };
+        let mut data = vec![0u8; 8 + TokenListEntry::INIT_SPACE];
+        entry.try_serialize(&mut &mut data[..]).unwrap();
+        data
+    }
+    
+    #[test]
+    fn token_lists_use_separate_entries_per_mint() {
+        let (dial_key, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        
+        assert_eq!(TokenList::Allow.other(), TokenList::Deny);
+        assert_eq!(TokenList::Deny.other(), TokenList::Allow);
+        assert_ne!(
+            token_list_address(TokenList::Allow, &dial_key, &mint),
+            token_list_address(TokenList::Deny, &dial_key, &mint)
+        );
+        assert_ne!(
+            token_list_address(TokenList::Allow, &dial_key, &mint),
+            token_list_address(TokenList::Allow, &Pubkey::new_unique(), &mint)
+        );
+    }
+    
+    #[test]
+    fn list_symbols_drop_the_dollar_sign() {
+        assert_eq!(normalize_symbol("$BONK").unwrap(), "BONK");
+        assert_eq!(normalize_symbol("wif").unwrap(), "wif");
+        for symbol in ["", "$", "$$BONK", "BO NK", "$ABCDEFGHIJK"] {
+            assert_eq!(normalize_symbol(symbol).unwrap_err(), DialError::InvalidSymbol.into());
+        }
+    }
+    
+    #[test]
+    fn listed_mints_follow_the_risk_policy() {
+        let (dial_key, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (allow_key, deny_key) = (
+            token_list_address(TokenList::Allow, &dial_key, &mint),
+            token_list_address(TokenList::Deny, &dial_key, &mint),
+        );
+        let strict = RiskPolicy { require_allowlist: true, ..Default::default() };
+        
+        let (mut listed_lamports, mut listed_data) = (1, list_entry_data(dial_key, mint));
+        let (mut unlisted_lamports, mut unlisted_data) = (0, []);
+        let listed = AccountInfo::new(
+            &allow_key, false, false, &mut listed_lamports, &mut listed_data, &crate::ID, false, 0,
+        );
+        let unlisted = AccountInfo::new(
+            &deny_key, false, false, &mut unlisted_lamports, &mut unlisted_data, &system_program::ID, false, 0,
+        );
+        
+        // An entry on one list is what blocks adding the mint to the other
+        assert!(list_entry_exists(&listed));
+        assert!(!list_entry_exists(&unlisted));
+        
+        assert!(check_token_lists(&strict, &mint, &listed, &unlisted).is_ok());
+        assert!(check_token_lists(&RiskPolicy::default(), &mint, &unlisted, &unlisted).is_ok());
+        assert_eq!(
+            check_token_lists(&strict, &mint, &unlisted, &unlisted).unwrap_err(),
+            DialError::MintNotAllowlisted.into()
+        );
+        assert_eq!(
+            check_token_lists(&RiskPolicy::default(), &mint, &unlisted, &listed).unwrap_err(),
+            DialError::MintDenied.into()
+        );
+    }
+}
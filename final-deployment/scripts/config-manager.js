@@ -144,7 +144,7 @@ class ConfigManager {
         return pda;
     }
     getSmartDialPda() {
-        const [pda] = web3_js_1.PublicKey.findProgramAddressSync([Buffer.from('dial_state')], this.getSmartDialProgramId());
+        const [pda] = web3_js_1.PublicKey.findProgramAddressSync([Buffer.from('dial_state'), this.getVaultPda().toBuffer()], this.getSmartDialProgramId());
         return pda;
     }
     getPoolRegistryPda() {
@@ -198,15 +198,37 @@ export class ConfigManager {
   
   getSmartDialPda(): PublicKey {
     const [pda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('dial_state')],
+      [Buffer.from('dial_state'), this.getVaultPda().toBuffer()],
       this.getSmartDialProgramId()
     );
     return pda;
   }
   
-  getPoolRegistryPda(): PublicKey {
+  // Page 0 keeps the original seeds, later pages append their u16 index
+  getPoolRegistryPda(page: number = 0): PublicKey {
+    const seeds = [Buffer.from('pool_registry'), this.getVaultPda().toBuffer()];
+    if (page > 0) {
+      const index = Buffer.alloc(2);
+      index.writeUInt16LE(page);
+      seeds.push(index);
+    }
+    const [pda] = PublicKey.findProgramAddressSync(seeds, this.getVaultProgramId());
+    return pda;
+  }
+  
+  getHolderTreasuryPda(): PublicKey {
+    const [pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('holder_treasury'), this.getVaultPda().toBuffer()],
+      this.getVaultProgramId()
+    );
+    return pda;
+  }
+  
+  getKeeperLogPda(page: number): PublicKey {
+    const index = Buffer.alloc(8);
+    index.writeBigUInt64LE(BigInt(page));
     const [pda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('pool_registry'), this.getVaultPda().toBuffer()],
+      [Buffer.from('keeper_log'), this.getVaultPda().toBuffer(), index],
       this.getVaultProgramId()
     );
     return pda;
@@ -30,6 +30,9 @@ import * as path from 'path';
 // Raydium CPMM Program ID
 const RAYDIUM_CPMM_PROGRAM_ID = new PublicKey('CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C');
 
+// Pools per registry page, matches the vault's MAX_POOLS
+const MAX_POOLS_PER_PAGE = 50;
+
 // Bootstrap stage parameters from LAUNCH_LIQUIDITY_PARAMS.md
 const BOOTSTRAP_MIKO_SEND = 47_370_000; // 47.37M MIKO to send (includes 5% tax)
 const BOOTSTRAP_MIKO_POOL = 45_000_000; // 45M MIKO arrives in pool after 5% tax
@@ -289,17 +292,33 @@ async function createPool() {
   console.log('Signature:', sig);
   console.log('Pool ID:', poolId.toBase58());
   
-  // Set launch time in vault
-  console.log('\nSetting launch time in vault...');
-  await setLaunchTime(connection, vaultProgramId, vaultPda, deployer, configManager);
+  // Token accounts start with their mint, pick the pool vault holding MIKO
+  const vaultAInfo = await connection.getAccountInfo(extInfo.address.vaultA);
+  const mikoIsA = !!vaultAInfo && new PublicKey(vaultAInfo.data.subarray(0, 32)).equals(tokenMint);
   
   // Update vault program with pool registry
   console.log('\nUpdating pool registry...');
-  await updatePoolRegistry(
+  const poolRegistryPage = await updatePoolRegistry(
+    connection,
+    vaultProgramId,
+    vaultPda,
+    {
+      pool: poolId,
+      dexProgram: RAYDIUM_CPMM_PROGRAM_ID,
+      tokenVault: mikoIsA ? extInfo.address.vaultA : extInfo.address.vaultB,
+      quoteMint: solMint,
+    },
+    configManager
+  );
+  
+  // Set launch time in vault, the registered pool proves liquidity exists
+  console.log('\nSetting launch time in vault...');
+  await setLaunchTime(
     connection,
     vaultProgramId,
     vaultPda,
-    poolId,
+    deployer,
+    poolRegistryPage ? { poolRegistry: poolRegistryPage, poolState: poolId } : null,
     configManager
   );
   
@@ -362,12 +381,14 @@ async function createPool() {
 
 /**
  * Set launch time in vault
+ * The deployer signs as vault authority, the launch time defaults to the on-chain clock
  */
 async function setLaunchTime(
   connection: Connection,
   vaultProgramId: PublicKey,
   vaultPda: PublicKey,
   deployer: Keypair,
+  proof: { poolRegistry: PublicKey; poolState: PublicKey } | null,
   configManager: any
 ) {
   try {
@@ -384,9 +405,12 @@ async function setLaunchTime(
     const program = new anchor.Program(idl, provider);
     
     const tx = await program.methods
-      .setLaunchTime()
-      .accounts({
+      .setLaunchTime(null)
+      .accountsPartial({
         vault: vaultPda,
+        signer: deployer.publicKey,
+        poolRegistry: proof?.poolRegistry ?? null,
+        poolState: proof?.poolState ?? null,
       })
       .rpc();
     
@@ -403,14 +427,15 @@ async function setLaunchTime(
 
 /**
  * Update vault program's pool registry
+ * The pool goes on the first page with room, other pages are passed for the duplicate check
  */
 async function updatePoolRegistry(
   connection: Connection,
   vaultProgramId: PublicKey,
   vaultPda: PublicKey,
-  poolId: PublicKey,
+  poolInfo: { pool: PublicKey; dexProgram: PublicKey; tokenVault: PublicKey; quoteMint: PublicKey },
   configManager: any
-) {
+): Promise<PublicKey | null> {
   try {
     const keeper = configManager.loadKeypair('keeper');
     const provider = new anchor.AnchorProvider(
@@ -425,22 +450,42 @@ async function updatePoolRegistry(
     idl.address = vaultProgramId.toBase58();
     const program = new anchor.Program(idl, provider);
     
-    const poolRegistryPda = configManager.getPoolRegistryPda();
+    const vaultState = await (program.account as any).vaultState.fetch(vaultPda);
+    const pages: PublicKey[] = [];
+    let targetPage = -1;
+    for (let page = 0; page < vaultState.poolRegistryPages; page++) {
+      const pda = configManager.getPoolRegistryPda(page);
+      const registry = await (program.account as any).poolRegistry.fetch(pda);
+      pages.push(pda);
+      if (targetPage < 0 && registry.pools.length < MAX_POOLS_PER_PAGE) {
+        targetPage = page;
+      }
+    }
+    if (targetPage < 0) {
+      throw new Error('All pool registry pages are full, initialize the next page first');
+    }
     
     const tx = await program.methods
-      .updatePoolRegistry([poolId])
-      .accounts({
-        poolRegistry: poolRegistryPda,
+      .updatePoolRegistry(targetPage, [poolInfo])
+      .accountsPartial({
+        poolRegistry: pages[targetPage],
         vault: vaultPda,
         keeperAuthority: keeper.publicKey,
       })
+      .remainingAccounts(
+        pages
+          .filter((_, page) => page !== targetPage)
+          .map(pubkey => ({ pubkey, isSigner: false, isWritable: false }))
+      )
       .signers([keeper])
       .rpc();
     
     console.log('✅ Pool registry updated!');
     console.log('Signature:', tx);
+    return pages[targetPage];
   } catch (error) {
     console.log('⚠️  Pool registry update failed (may require manual update):', error);
+    return null;
   }
 }
 
@@ -35,7 +35,7 @@ async function initializeSmartDial() {
   const smartDialProgramId = configManager.getSmartDialProgramId();
   
   console.log('Configuration:');
-  console.log('- Deployer (Authority):', deployer.publicKey.toBase58());
+  console.log('- Deployer (Authority, must be the vault authority):', deployer.publicKey.toBase58());
   console.log('- Smart Dial Program:', smartDialProgramId.toBase58());
   
   // Create program interface
@@ -50,13 +50,15 @@ async function initializeSmartDial() {
   smartDialIDL.address = smartDialProgramId.toBase58();
   const program = new Program(smartDialIDL, provider) as any;
   
-  // Derive PDA
+  // Derive PDA, one dial per vault
+  const vaultPda = configManager.getVaultPda();
   const [dialStatePda, dialBump] = PublicKey.findProgramAddressSync(
-    [Buffer.from('dial_state')],
+    [Buffer.from('dial_state'), vaultPda.toBuffer()],
     smartDialProgramId
   );
   
-  console.log('\nSmart Dial PDA:', dialStatePda.toBase58());
+  console.log('\nVault PDA:', vaultPda.toBase58());
+  console.log('Smart Dial PDA:', dialStatePda.toBase58());
   
   // Check if already initialized
   try {
@@ -92,6 +94,7 @@ async function initializeSmartDial() {
       .initialize(new BN(launchTimestamp))
       .accounts({
         dialState: dialStatePda,
+        vault: vaultPda,
         authority: deployer.publicKey,
         payer: deployer.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
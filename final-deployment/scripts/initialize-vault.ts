@@ -70,6 +70,18 @@ async function initializeVault() {
     vaultProgramId
   );
   
+  // Exclusion markers created at initialize, one per distinct system wallet in program order
+  const exclusionMarkers = [deployer.publicKey, keeper.publicKey, owner.publicKey, vaultPda, vaultProgramId]
+    .filter((wallet, i, wallets) => wallets.findIndex(other => other.equals(wallet)) === i)
+    .map(wallet => ({
+      pubkey: PublicKey.findProgramAddressSync(
+        [Buffer.from('exclusion'), vaultPda.toBuffer(), wallet.toBuffer()],
+        vaultProgramId
+      )[0],
+      isSigner: false,
+      isWritable: true,
+    }));
+  
   console.log('\nPDAs:');
   console.log('- Vault PDA:', vaultPda.toBase58());
   console.log('- Pool Registry PDA:', poolRegistryPda.toBase58());
@@ -114,6 +126,7 @@ async function initializeVault() {
         payer: deployer.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
+      .remainingAccounts(exclusionMarkers)
       .instruction();
     
     tx.add(initVaultIx);
@@ -154,9 +167,10 @@ async function initializeVault() {
       );
     }
     
+    // Page 0 uses the original registry seeds
     const initRegistryIx = await program.methods
-      .initializePoolRegistry()
-      .accounts({
+      .initializePoolRegistry(0)
+      .accountsPartial({
         poolRegistry: poolRegistryPda,
         vault: vaultPda,
         payer: deployer.publicKey,
@@ -23,7 +23,9 @@ import * as fs from 'fs';
 import * as path from 'path';
 
 /**
- * Withdraw withheld fees from deployment pool vault using emergency_withdraw_withheld
+ * Withdraw withheld fees from deployment pool vault to the deployer
+ * Fees are collected into the vault, then an EmergencyWithdraw config change is queued.
+ * Run again with `execute <id>` once the emergency delay has passed.
  */
 async function withdrawPoolFees() {
   console.log('=== Withdraw Pool Fees from Deployment Pool ===\n');
@@ -230,12 +232,13 @@ async function withdrawPoolFees() {
     ComputeBudgetProgram.setComputeUnitPrice({ microLamports: priorityFee.microLamports })
   );
   
-  // First withdraw to vault
+  // First withdraw to vault, the authority co-signs to override the harvest threshold
   const vaultWithdrawIx = await program.methods
     .withdrawFeesFromMint()
     .accounts({
       vault: vaultPda,
       keeperAuthority: keeper.publicKey,
+      authority: deployer.publicKey,
       tokenMint: tokenMint,
       vaultTokenAccount: vaultAta,
       tokenProgram: TOKEN_2022_PROGRAM_ID,
@@ -244,20 +247,25 @@ async function withdrawPoolFees() {
   
   withdrawTx.add(vaultWithdrawIx);
   
-  // Then transfer from vault to deployer
-  const transferIx = await program.methods
-    .emergencyWithdrawVault(new BN(withheldAmount.toString()))
-    .accounts({
+  // Then queue the transfer from vault to deployer behind the emergency timelock
+  const vaultState = await (program.account as any).vaultState.fetch(vaultPda);
+  const changeId: BN = vaultState.configChangeCount;
+  const queueIx = await program.methods
+    .queueConfigChange({
+      emergencyWithdraw: {
+        amount: new BN(withheldAmount.toString()),
+        destination: deployerAta,
+      },
+    })
+    .accountsPartial({
       vault: vaultPda,
-      authority: deployer.publicKey,
-      vaultTokenAccount: vaultAta,
-      destinationTokenAccount: deployerAta,
-      tokenMint: tokenMint,
-      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      admin: deployer.publicKey,
+      pendingChange: pendingChangePda(vaultProgramId, vaultPda, changeId),
+      systemProgram: anchor.web3.SystemProgram.programId,
     })
     .instruction();
   
-  withdrawTx.add(transferIx);
+  withdrawTx.add(queueIx);
   
   const sig = await sendAndConfirmTransaction(
     connection,
@@ -266,12 +274,16 @@ async function withdrawPoolFees() {
     { commitment: 'confirmed' }
   );
   
-  console.log('\n✅ Withheld fees withdrawn!');
+  const executeAfter = vaultState.emergencyDelay.toNumber() + Math.floor(Date.now() / 1000);
+  console.log('\n✅ Withheld fees moved to vault, emergency withdrawal queued');
   console.log('Signature:', sig);
   console.log('Amount:', Number(withheldAmount) / 1e9, 'MIKO');
   console.log('Recipient:', deployerAta.toBase58());
+  console.log('Config change id:', changeId.toString());
+  console.log('Executable after:', new Date(executeAfter * 1000).toISOString());
+  console.log(`Run again with \`execute ${changeId.toString()}\` once the delay has passed`);
   
-  // Verify the withdrawal
+  // Verify the pool vault was harvested
   const finalVaultInfo = await connection.getAccountInfo(mikoVault);
   let finalWithheld = BigInt(0);
   
@@ -284,11 +296,90 @@ async function withdrawPoolFees() {
   }
   
   console.log('\nVerification:');
-  console.log('Final withheld in vault:', Number(finalWithheld) / 1e9, 'MIKO');
+  console.log('Final withheld in pool vault:', Number(finalWithheld) / 1e9, 'MIKO');
+}
+
+function pendingChangePda(programId: PublicKey, vaultPda: PublicKey, id: BN): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from('pending_config'), vaultPda.toBuffer(), id.toArrayLike(Buffer, 'le', 8)],
+    programId
+  );
+  return pda;
+}
+
+/**
+ * Execute a queued emergency withdrawal once its delay has passed
+ */
+async function executePoolFeeWithdrawal(id: BN) {
+  console.log(`=== Execute Emergency Withdrawal ${id.toString()} ===\n`);
+  
+  const configManager = getConfigManager();
+  const connection = configManager.getConnection();
+  const deployer = configManager.loadKeypair('deployer');
+  const tokenMint = configManager.getTokenMint();
+  const vaultProgramId = configManager.getVaultProgramId();
+  const vaultPda = configManager.getVaultPda();
+  
+  const provider = new anchor.AnchorProvider(
+    connection,
+    new anchor.Wallet(deployer),
+    { commitment: configManager.getCommitment() }
+  );
+  const idlPath = path.join(__dirname, '..', 'idl', 'absolute_vault.json');
+  const program = new anchor.Program(JSON.parse(fs.readFileSync(idlPath, 'utf8')), provider);
+  
+  const pendingChange = pendingChangePda(vaultProgramId, vaultPda, id);
+  const pending = await (program.account as any).pendingConfigChange.fetch(pendingChange);
+  const withdrawal = pending.change.emergencyWithdraw;
+  if (!withdrawal) {
+    throw new Error(`Config change ${id.toString()} is not an emergency withdrawal`);
+  }
+  
+  const vaultAta = getAssociatedTokenAddressSync(tokenMint, vaultPda, true, TOKEN_2022_PROGRAM_ID);
+  
+  // The stricter of the queued and current signer thresholds applies, approve first if one is set
+  const vaultState = await (program.account as any).vaultState.fetch(vaultPda);
+  const needed = Math.max(pending.requiredApprovals, vaultState.adminThreshold);
+  const approvals: PublicKey[] = pending.approvals;
+  if (needed > 0 && !approvals.some(a => a.equals(deployer.publicKey))) {
+    const approveSig = await program.methods
+      .approveConfigChange()
+      .accountsPartial({
+        vault: vaultPda,
+        signer: deployer.publicKey,
+        pendingChange,
+      })
+      .rpc();
+    console.log('Approved config change. Signature:', approveSig);
+  }
+  
+  // Accounts the emergency withdrawal does not touch are passed as None
+  const sig = await program.methods
+    .executeConfigChange()
+    .accountsPartial({
+      vault: vaultPda,
+      pendingChange,
+      proposer: pending.proposer,
+      executor: deployer.publicKey,
+      tokenMint,
+      vaultTokenAccount: vaultAta,
+      destinationTokenAccount: withdrawal.destination,
+      holderTreasury: null,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      dexAllowlist: null,
+      feeHistory: null,
+      exclusionMarker: null,
+      authority: null,
+      systemProgram: null,
+    })
+    .rpc();
+  
+  console.log('✅ Emergency withdrawal executed! Signature:', sig);
+  console.log('Amount:', withdrawal.amount.toNumber() / 1e9, 'MIKO');
   
   const deployerBalance = await getAccount(
     connection,
-    deployerAta,
+    withdrawal.destination,
     'confirmed',
     TOKEN_2022_PROGRAM_ID
   );
@@ -297,7 +388,11 @@ async function withdrawPoolFees() {
 
 // Run if called directly
 if (require.main === module) {
-  withdrawPoolFees()
+  const [mode, id] = process.argv.slice(2);
+  const run = mode === 'execute'
+    ? executePoolFeeWithdrawal(new BN(id))
+    : withdrawPoolFees();
+  run
     .then(() => {
       console.log('\n✅ Success!');
       process.exit(0);
@@ -308,4 +403,4 @@ if (require.main === module) {
     });
 }
 
-export { withdrawPoolFees };
\ No newline at end of file
+export { withdrawPoolFees, executePoolFeeWithdrawal };
\ No newline at end of file
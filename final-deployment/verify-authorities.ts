@@ -65,7 +65,7 @@ async function verifyAuthorities() {
   
   // Find dial state PDA
   const [dialStatePda] = PublicKey.findProgramAddressSync(
-    [Buffer.from('dial_state')],
+    [Buffer.from('dial_state'), vaultPda.toBuffer()],
     dialProgramId
   );
   